@@ -17,7 +17,8 @@
 //! Provides runtime implementations for map and array operations
 
 use std::collections::HashMap;
-use std::os::raw::c_void;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int, c_void};
 use std::ptr;
 
 /// Simple map structure for runtime
@@ -26,6 +27,12 @@ pub struct AetherMap {
     // For simplicity, we'll use a HashMap internally
     // In a real implementation, this would be more sophisticated
     data: *mut HashMap<i32, i32>,
+    /// Entries of a constant map the compiler emitted as static data, or
+    /// null for a map built at run time. The table is a run of i64 slots:
+    /// the entry count, 1 if keys are strings (else 0), then each key
+    /// followed by its value. String keys are pointers to C strings and
+    /// compare by content.
+    table: *const i64,
 }
 
 /// Create a new map
@@ -33,10 +40,35 @@ pub struct AetherMap {
 pub extern "C" fn map_new() -> *mut c_void {
     let map = Box::new(AetherMap {
         data: Box::into_raw(Box::new(HashMap::new())),
+        table: ptr::null(),
     });
     Box::into_raw(map) as *mut c_void
 }
 
+/// Look `key` up in a static map table
+unsafe fn table_lookup(table: *const i64, key: *const c_void) -> Option<i32> {
+    let count = *table as usize;
+    let string_keys = *table.add(1) != 0;
+    (0..count).find_map(|i| {
+        let entry_key = *table.add(2 + 2 * i);
+        let matches = if string_keys {
+            let wanted = *(key as *const *const c_char);
+            !wanted.is_null() && CStr::from_ptr(entry_key as *const c_char) == CStr::from_ptr(wanted)
+        } else {
+            entry_key as i32 == *(key as *const i32)
+        };
+        matches.then(|| *table.add(3 + 2 * i) as i32)
+    })
+}
+
+/// Look `key` up in whichever storage the map has
+unsafe fn map_lookup(map: &AetherMap, key: *const c_void) -> Option<i32> {
+    if !map.table.is_null() {
+        return table_lookup(map.table, key);
+    }
+    map.data.as_ref()?.get(&*(key as *const i32)).copied()
+}
+
 /// Insert a key-value pair into the map (generic version)
 #[no_mangle]
 pub extern "C" fn map_insert(map: *mut c_void, key: *const c_void, value: *const c_void) {
@@ -65,25 +97,30 @@ pub extern "C" fn map_get(map: *const c_void, key: *const c_void) -> *mut c_void
     
     unsafe {
         let map_ref = &*(map as *const AetherMap);
-        if let Some(hashmap) = map_ref.data.as_ref() {
-            let key_val = *(key as *const i32);
-            if let Some(value) = (*hashmap).get(&key_val) {
-                // Allocate memory for the result and return it
-                let result = Box::new(*value);
-                Box::into_raw(result) as *mut c_void
-            } else {
-                std::ptr::null_mut()
-            }
+        if let Some(value) = map_lookup(map_ref, key.cast()) {
+            // Allocate memory for the result and return it
+            let result = Box::new(value);
+            Box::into_raw(result) as *mut c_void
         } else {
             std::ptr::null_mut()
         }
     }
 }
 
-/// Delete a map and free its memory
+/// Whether the map holds a value for the key: 1 if it does, 0 otherwise
+#[no_mangle]
+pub extern "C" fn map_contains(map: *const c_void, key: *const c_void) -> c_int {
+    if map.is_null() || key.is_null() {
+        return 0;
+    }
+    
+    unsafe { map_lookup(&*(map as *const AetherMap), key.cast()).is_some() as c_int }
+}
+
+/// Delete a map and free its memory. Static maps are left alone.
 #[no_mangle]
 pub extern "C" fn map_delete(map: *mut AetherMap) {
-    if !map.is_null() {
+    if !map.is_null() && unsafe { (*map.cast_const()).table.is_null() } {
         unsafe {
             let map_box = Box::from_raw(map);
             if !map_box.data.is_null() {
@@ -170,15 +207,7 @@ pub extern "C" fn aether_collections_map_contains(
         return false;
     }
     
-    unsafe {
-        let map_ref = &*(map as *const AetherMap);
-        if let Some(hashmap) = map_ref.data.as_ref() {
-            let key_val = *(key as *const i32);
-            (*hashmap).contains_key(&key_val)
-        } else {
-            false
-        }
-    }
+    unsafe { map_lookup(&*(map as *const AetherMap), key.cast()).is_some() }
 }
 
 /// Remove a key from the map
@@ -211,7 +240,9 @@ pub extern "C" fn aether_collections_map_size(map: *const c_void) -> usize {
     
     unsafe {
         let map_ref = &*(map as *const AetherMap);
-        if let Some(hashmap) = map_ref.data.as_ref() {
+        if !map_ref.table.is_null() {
+            *map_ref.table as usize
+        } else if let Some(hashmap) = map_ref.data.as_ref() {
             (*hashmap).len()
         } else {
             0
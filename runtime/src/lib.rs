@@ -133,6 +133,23 @@ pub extern "C" fn aether_panic(message: *const c_char) {
     panic!("{}", msg);
 }
 
+/// Report a violated contract and continue execution
+#[no_mangle]
+pub extern "C" fn contract_warn(message: *const c_char) {
+    let msg = if message.is_null() {
+        "contract violated"
+    } else {
+        unsafe {
+            match CStr::from_ptr(message).to_str() {
+                Ok(s) => s,
+                Err(_) => "contract violated (invalid UTF-8 message)",
+            }
+        }
+    };
+    
+    eprintln!("Warning: {}", msg);
+}
+
 pub mod network;
 pub mod memory;
 pub mod memory_alloc;
@@ -244,6 +261,26 @@ pub unsafe extern "C" fn array_length(array_ptr: *mut c_void) -> c_int {
     (*array).length
 }
 
+/// Copy `length` elements starting at `start` into a new array
+#[no_mangle]
+pub unsafe extern "C" fn array_slice(array_ptr: *mut c_void, start: c_int, length: c_int) -> *mut c_void {
+    if array_ptr.is_null() {
+        return ptr::null_mut();
+    }
+    
+    // Clamp the range to the source array
+    let source_length = array_length(array_ptr);
+    let start = start.clamp(0, source_length);
+    let length = length.clamp(0, source_length - start);
+    
+    let slice_ptr = array_create(length);
+    for i in 0..length {
+        array_set(slice_ptr, i, array_get(array_ptr, start + i));
+    }
+    
+    slice_ptr
+}
+
 /// Free an array
 #[no_mangle]
 pub unsafe extern "C" fn array_free(array_ptr: *mut c_void) {
@@ -732,6 +769,67 @@ pub unsafe extern "C" fn string_substring(str: *const c_char, start: c_int, leng
     substring(str, start, length)
 }
 
+/// String length in Unicode code points
+#[no_mangle]
+pub unsafe extern "C" fn string_length_chars(str: *const c_char) -> c_int {
+    if str.is_null() {
+        return 0;
+    }
+    
+    match CStr::from_ptr(str).to_str() {
+        Ok(s) => s.chars().count() as c_int,
+        Err(_) => 0,
+    }
+}
+
+/// Get the code point at a code point index. `Char` is a single byte, so
+/// characters outside ASCII come back as `?`
+#[no_mangle]
+pub unsafe extern "C" fn string_char_at_chars(str: *const c_char, index: c_int) -> c_char {
+    if str.is_null() || index < 0 {
+        return 0;
+    }
+    
+    let s = match CStr::from_ptr(str).to_str() {
+        Ok(s) => s,
+        Err(_) => return 0,
+    };
+    
+    match s.chars().nth(index as usize) {
+        Some(c) if c.is_ascii() => c as c_char,
+        Some(_) => b'?' as c_char,
+        None => 0,
+    }
+}
+
+/// Substring extraction with start and length counted in code points
+#[no_mangle]
+pub unsafe extern "C" fn string_substring_chars(str: *const c_char, start: c_int, length: c_int) -> *mut c_char {
+    if str.is_null() || start < 0 || length <= 0 {
+        return ptr::null_mut();
+    }
+    
+    let s = match CStr::from_ptr(str).to_str() {
+        Ok(s) => s,
+        Err(_) => return ptr::null_mut(),
+    };
+    
+    let substr: String = s.chars().skip(start as usize).take(length as usize).collect();
+    if substr.is_empty() {
+        return ptr::null_mut();
+    }
+    let result = format!("{}\0", substr);
+    
+    let len = result.len();
+    let ptr = crate::memory_alloc::aether_safe_malloc(len) as *mut c_char;
+    
+    if !ptr.is_null() {
+        ptr::copy_nonoverlapping(result.as_ptr() as *const c_char, ptr, len);
+    }
+    
+    ptr
+}
+
 /// Alias for string_to_int to match stdlib name
 #[no_mangle]
 pub unsafe extern "C" fn parse_int(str: *const c_char) -> c_int {
@@ -859,6 +957,48 @@ pub unsafe extern "C" fn aether_drop_value(value_ptr: *mut c_void, type_id: c_in
 mod tests {
     use super::*;
     
+    #[test]
+    fn test_map_contains() {
+        let map = collections::map_new();
+        let (key, value, missing) = (7i32, 42i32, 8i32);
+        collections::map_insert(map, &key as *const i32 as *const c_void, &value as *const i32 as *const c_void);
+        
+        assert_eq!(collections::map_contains(map, &key as *const i32 as *const c_void), 1);
+        assert_eq!(collections::map_contains(map, &missing as *const i32 as *const c_void), 0);
+        assert_eq!(collections::map_contains(std::ptr::null(), &key as *const i32 as *const c_void), 0);
+        
+        collections::map_delete(map as *mut collections::AetherMap);
+    }
+    
+    #[test]
+    fn test_static_map_table_lookup() {
+        // What the compiler emits for a constant map: no hash map, only a table
+        #[repr(C)]
+        struct StaticMap {
+            data: *mut c_void,
+            table: *const i64,
+        }
+        let (one, two) = (c"one", c"two");
+        let table = [2, 1, one.as_ptr() as i64, 1, two.as_ptr() as i64, 2];
+        let mut map = StaticMap { data: std::ptr::null_mut(), table: table.as_ptr() };
+        let map = &mut map as *mut StaticMap as *mut c_void;
+        
+        let key_ptr = c"two".as_ptr();
+        let value = collections::map_get(map, &key_ptr as *const *const c_char as *const c_void);
+        assert_eq!(unsafe { *(value as *const i32) }, 2);
+        unsafe { drop(Box::from_raw(value as *mut i32)) };
+        
+        let missing = c"three".as_ptr();
+        assert_eq!(collections::map_contains(map, &missing as *const *const c_char as *const c_void), 0);
+        assert_eq!(collections::aether_collections_map_size(map), 2);
+        
+        // Inserting into or deleting a static map does nothing
+        let (new_key, new_value) = (3i32, 3i32);
+        collections::map_insert(map, &new_key as *const i32 as *const c_void, &new_value as *const i32 as *const c_void);
+        collections::map_delete(map as *mut collections::AetherMap);
+        assert_eq!(collections::aether_collections_map_size(map), 2);
+    }
+    
     #[test]
     fn test_array_operations() {
         unsafe {
@@ -886,6 +1026,30 @@ mod tests {
         }
     }
     
+    #[test]
+    fn test_array_slice() {
+        unsafe {
+            let array = array_create(5);
+            for i in 0..5 {
+                array_set(array, i, (i + 1) * 10);
+            }
+            
+            let slice = array_slice(array, 1, 3);
+            assert_eq!(array_length(slice), 3);
+            assert_eq!(array_get(slice, 0), 20);
+            assert_eq!(array_get(slice, 2), 40);
+            
+            // Out-of-range requests are clamped to the source array
+            let tail = array_slice(array, 3, 10);
+            assert_eq!(array_length(tail), 2);
+            assert_eq!(array_get(tail, 1), 50);
+            
+            array_free(tail);
+            array_free(slice);
+            array_free(array);
+        }
+    }
+    
     #[test]
     fn test_string_index_of() {
         unsafe {
@@ -1104,6 +1268,17 @@ mod tests {
             assert_eq!(sub_str, "World");
             string_free(sub);
             
+            // Test the code point variants
+            let accented = "h\u{e9}llo\0".as_ptr() as *const c_char;
+            assert_eq!(string_length(accented), 6);
+            assert_eq!(string_length_chars(accented), 5);
+            assert_eq!(string_char_at_chars(accented, 1), b'?' as c_char);
+            assert_eq!(string_char_at_chars(accented, 2), b'l' as c_char);
+            let sub = string_substring_chars(accented, 1, 3);
+            assert!(!sub.is_null());
+            assert_eq!(CStr::from_ptr(sub).to_str().unwrap(), "\u{e9}ll");
+            string_free(sub);
+            
             // Test to_string alias
             let result = to_string(123);
             assert!(!result.is_null());
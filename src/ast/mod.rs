@@ -210,7 +210,7 @@ pub enum OwnershipKind {
 }
 
 /// Primitive type names
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum PrimitiveType {
     Integer,
     Integer32,
@@ -325,6 +325,26 @@ pub struct FunctionMetadata {
     pub throws_exceptions: Vec<Box<TypeSpecifier>>,
     pub thread_safe: Option<bool>,
     pub may_block: Option<bool>,
+    pub pure: Option<bool>,
+    /// Optimization passes the function opts out of, and inlining and
+    /// placement hints
+    pub optimization_hints: Vec<OptimizationHint>,
+}
+
+/// Per-function control over optimization, for debugging miscompiles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OptimizationHint {
+    /// Run no optimization passes on the function, and never inline it
+    None,
+    /// Never inline the function into its callers
+    NoInline,
+    /// Inline the function into every caller, whatever its size
+    InlineAlways,
+    /// The function rarely runs, so optimize it for size and move it out of
+    /// the way of hot code
+    Cold,
+    /// The function runs often, so optimize it aggressively
+    Hot,
 }
 
 /// Contract assertion (precondition, postcondition, invariant)
@@ -499,6 +519,15 @@ pub enum Statement {
         label: Option<Identifier>,
         source_location: SourceLocation,
     },
+    /// Loop for as long as a value, evaluated again before each iteration,
+    /// matches a pattern; the pattern's bindings are in scope in the body
+    WhileMatchLoop {
+        value: Box<Expression>,
+        pattern: Pattern,
+        body: Block,
+        label: Option<Identifier>,
+        source_location: SourceLocation,
+    },
     ForEachLoop {
         collection: Box<Expression>,
         element_binding: Identifier,
@@ -536,6 +565,13 @@ pub enum Statement {
         exception: Box<Expression>,
         source_location: SourceLocation,
     },
+    /// Evaluate an expression when control leaves the enclosing block, by
+    /// falling through, returning, breaking or continuing; deferred
+    /// expressions run in the reverse order of their declarations
+    Defer {
+        expression: Box<Expression>,
+        source_location: SourceLocation,
+    },
     ResourceScope {
         scope: resource::ResourceScope,
         source_location: SourceLocation,
@@ -546,6 +582,30 @@ pub enum Statement {
     },
 }
 
+impl Statement {
+    /// Source location of this statement
+    pub fn source_location(&self) -> &SourceLocation {
+        match self {
+            Statement::VariableDeclaration { source_location, .. }
+            | Statement::Assignment { source_location, .. }
+            | Statement::FunctionCall { source_location, .. }
+            | Statement::Return { source_location, .. }
+            | Statement::If { source_location, .. }
+            | Statement::WhileLoop { source_location, .. }
+            | Statement::WhileMatchLoop { source_location, .. }
+            | Statement::ForEachLoop { source_location, .. }
+            | Statement::FixedIterationLoop { source_location, .. }
+            | Statement::Break { source_location, .. }
+            | Statement::Continue { source_location, .. }
+            | Statement::TryBlock { source_location, .. }
+            | Statement::Throw { source_location, .. }
+            | Statement::Defer { source_location, .. }
+            | Statement::ResourceScope { source_location, .. }
+            | Statement::Expression { source_location, .. } => source_location,
+        }
+    }
+}
+
 /// Variable mutability
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Mutability {
@@ -599,6 +659,8 @@ pub enum Expression {
     // Literals
     IntegerLiteral {
         value: i64,
+        /// Type named by a suffix such as `i64`; unsuffixed literals are `INTEGER`
+        suffix: Option<PrimitiveType>,
         source_location: SourceLocation,
     },
     FloatLiteral {
@@ -668,6 +730,33 @@ pub enum Expression {
         source_location: SourceLocation,
     },
 
+    // Bitwise expressions
+    BitwiseAnd {
+        left: Box<Expression>,
+        right: Box<Expression>,
+        source_location: SourceLocation,
+    },
+    BitwiseOr {
+        left: Box<Expression>,
+        right: Box<Expression>,
+        source_location: SourceLocation,
+    },
+    BitwiseXor {
+        left: Box<Expression>,
+        right: Box<Expression>,
+        source_location: SourceLocation,
+    },
+    ShiftLeft {
+        left: Box<Expression>,
+        right: Box<Expression>,
+        source_location: SourceLocation,
+    },
+    ShiftRight {
+        left: Box<Expression>,
+        right: Box<Expression>,
+        source_location: SourceLocation,
+    },
+
     // Comparison predicates
     Equals {
         left: Box<Expression>,
@@ -719,6 +808,11 @@ pub enum Expression {
         operands: Vec<Expression>,
         source_location: SourceLocation,
     },
+    /// String literal with embedded expressions, e.g. "x = {x}"
+    InterpolatedString {
+        parts: Vec<InterpolatedPart>,
+        source_location: SourceLocation,
+    },
     StringLength {
         string: Box<Expression>,
         source_location: SourceLocation,
@@ -752,6 +846,16 @@ pub enum Expression {
         failure_behavior: CastFailureBehavior,
         source_location: SourceLocation,
     },
+    
+    // Type layout, as compile-time integers
+    SizeOf {
+        target_type: Box<TypeSpecifier>,
+        source_location: SourceLocation,
+    },
+    AlignOf {
+        target_type: Box<TypeSpecifier>,
+        source_location: SourceLocation,
+    },
 
     // Function calls
     FunctionCall {
@@ -770,6 +874,14 @@ pub enum Expression {
         index: Box<Expression>,
         source_location: SourceLocation,
     },
+    /// Range slice of an array or string; `end` is excluded unless `inclusive`
+    Slice {
+        collection: Box<Expression>,
+        start: Box<Expression>,
+        end: Box<Expression>,
+        inclusive: bool,
+        source_location: SourceLocation,
+    },
     MapAccess {
         map: Box<Expression>,
         key: Box<Expression>,
@@ -796,6 +908,14 @@ pub enum Expression {
         source_location: SourceLocation,
     },
 
+    // Ownership conversions
+    /// Borrow an owned or borrowed value without moving it
+    Borrow {
+        value: Box<Expression>,
+        mutable: bool,
+        source_location: SourceLocation,
+    },
+
     // Construction
     StructConstruct {
         type_name: Identifier,
@@ -828,6 +948,145 @@ pub enum Expression {
         value: Option<Box<Expression>>,
         source_location: SourceLocation,
     },
+    // Error propagation: unwraps the `Ok`/`Some` variant, or returns the
+    // other variant from the enclosing function
+    Try {
+        value: Box<Expression>,
+        source_location: SourceLocation,
+    },
+}
+
+impl Expression {
+    /// Source location covered by this expression
+    pub fn source_location(&self) -> &SourceLocation {
+        match self {
+            Expression::IntegerLiteral { source_location, .. }
+            | Expression::FloatLiteral { source_location, .. }
+            | Expression::StringLiteral { source_location, .. }
+            | Expression::CharacterLiteral { source_location, .. }
+            | Expression::BooleanLiteral { source_location, .. }
+            | Expression::NullLiteral { source_location, .. }
+            | Expression::Variable { source_location, .. }
+            | Expression::EnumMember { source_location, .. }
+            | Expression::Add { source_location, .. }
+            | Expression::Subtract { source_location, .. }
+            | Expression::Multiply { source_location, .. }
+            | Expression::Divide { source_location, .. }
+            | Expression::IntegerDivide { source_location, .. }
+            | Expression::Modulo { source_location, .. }
+            | Expression::Negate { source_location, .. }
+            | Expression::BitwiseAnd { source_location, .. }
+            | Expression::BitwiseOr { source_location, .. }
+            | Expression::BitwiseXor { source_location, .. }
+            | Expression::ShiftLeft { source_location, .. }
+            | Expression::ShiftRight { source_location, .. }
+            | Expression::Equals { source_location, .. }
+            | Expression::NotEquals { source_location, .. }
+            | Expression::LessThan { source_location, .. }
+            | Expression::LessThanOrEqual { source_location, .. }
+            | Expression::GreaterThan { source_location, .. }
+            | Expression::GreaterThanOrEqual { source_location, .. }
+            | Expression::LogicalAnd { source_location, .. }
+            | Expression::LogicalOr { source_location, .. }
+            | Expression::LogicalNot { source_location, .. }
+            | Expression::StringConcat { source_location, .. }
+            | Expression::InterpolatedString { source_location, .. }
+            | Expression::StringLength { source_location, .. }
+            | Expression::StringCharAt { source_location, .. }
+            | Expression::Substring { source_location, .. }
+            | Expression::StringEquals { source_location, .. }
+            | Expression::StringContains { source_location, .. }
+            | Expression::TypeCast { source_location, .. }
+            | Expression::SizeOf { source_location, .. }
+            | Expression::AlignOf { source_location, .. }
+            | Expression::FunctionCall { source_location, .. }
+            | Expression::FieldAccess { source_location, .. }
+            | Expression::ArrayAccess { source_location, .. }
+            | Expression::Slice { source_location, .. }
+            | Expression::MapAccess { source_location, .. }
+            | Expression::ArrayLength { source_location, .. }
+            | Expression::AddressOf { source_location, .. }
+            | Expression::Dereference { source_location, .. }
+            | Expression::PointerArithmetic { source_location, .. }
+            | Expression::Borrow { source_location, .. }
+            | Expression::StructConstruct { source_location, .. }
+            | Expression::ArrayLiteral { source_location, .. }
+            | Expression::MapLiteral { source_location, .. }
+            | Expression::Match { source_location, .. }
+            | Expression::EnumVariant { source_location, .. }
+            | Expression::Try { source_location, .. } => source_location,
+        }
+    }
+
+    /// Mutable access to the source location covered by this expression
+    pub fn source_location_mut(&mut self) -> &mut SourceLocation {
+        match self {
+            Expression::IntegerLiteral { source_location, .. }
+            | Expression::FloatLiteral { source_location, .. }
+            | Expression::StringLiteral { source_location, .. }
+            | Expression::CharacterLiteral { source_location, .. }
+            | Expression::BooleanLiteral { source_location, .. }
+            | Expression::NullLiteral { source_location, .. }
+            | Expression::Variable { source_location, .. }
+            | Expression::EnumMember { source_location, .. }
+            | Expression::Add { source_location, .. }
+            | Expression::Subtract { source_location, .. }
+            | Expression::Multiply { source_location, .. }
+            | Expression::Divide { source_location, .. }
+            | Expression::IntegerDivide { source_location, .. }
+            | Expression::Modulo { source_location, .. }
+            | Expression::Negate { source_location, .. }
+            | Expression::BitwiseAnd { source_location, .. }
+            | Expression::BitwiseOr { source_location, .. }
+            | Expression::BitwiseXor { source_location, .. }
+            | Expression::ShiftLeft { source_location, .. }
+            | Expression::ShiftRight { source_location, .. }
+            | Expression::Equals { source_location, .. }
+            | Expression::NotEquals { source_location, .. }
+            | Expression::LessThan { source_location, .. }
+            | Expression::LessThanOrEqual { source_location, .. }
+            | Expression::GreaterThan { source_location, .. }
+            | Expression::GreaterThanOrEqual { source_location, .. }
+            | Expression::LogicalAnd { source_location, .. }
+            | Expression::LogicalOr { source_location, .. }
+            | Expression::LogicalNot { source_location, .. }
+            | Expression::StringConcat { source_location, .. }
+            | Expression::InterpolatedString { source_location, .. }
+            | Expression::StringLength { source_location, .. }
+            | Expression::StringCharAt { source_location, .. }
+            | Expression::Substring { source_location, .. }
+            | Expression::StringEquals { source_location, .. }
+            | Expression::StringContains { source_location, .. }
+            | Expression::TypeCast { source_location, .. }
+            | Expression::SizeOf { source_location, .. }
+            | Expression::AlignOf { source_location, .. }
+            | Expression::FunctionCall { source_location, .. }
+            | Expression::FieldAccess { source_location, .. }
+            | Expression::ArrayAccess { source_location, .. }
+            | Expression::Slice { source_location, .. }
+            | Expression::MapAccess { source_location, .. }
+            | Expression::ArrayLength { source_location, .. }
+            | Expression::AddressOf { source_location, .. }
+            | Expression::Dereference { source_location, .. }
+            | Expression::PointerArithmetic { source_location, .. }
+            | Expression::Borrow { source_location, .. }
+            | Expression::StructConstruct { source_location, .. }
+            | Expression::ArrayLiteral { source_location, .. }
+            | Expression::MapLiteral { source_location, .. }
+            | Expression::Match { source_location, .. }
+            | Expression::EnumVariant { source_location, .. }
+            | Expression::Try { source_location, .. } => source_location,
+        }
+    }
+}
+
+/// One piece of an interpolated string
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum InterpolatedPart {
+    /// Literal text, with `{{` and `}}` already reduced to single braces
+    Literal(String),
+    /// Embedded expression, converted to a string when lowered
+    Expression(Box<Expression>),
 }
 
 /// Cast failure behavior
@@ -913,6 +1172,13 @@ pub enum Pattern {
         binding: Option<Identifier>, // Variable to bind the matched value
         source_location: SourceLocation,
     },
+    /// Match an integer range, `start..end` or `start..=end`
+    Range {
+        start: Box<Expression>,
+        end: Box<Expression>,
+        inclusive: bool,
+        source_location: SourceLocation,
+    },
 }
 
 /// Identifier representation
@@ -928,6 +1194,63 @@ impl Identifier {
     }
 }
 
+impl Module {
+    /// Prefixes through which the exports of each import are reachable, as
+    /// `(prefix, module path)` pairs: the alias, or else the full module
+    /// path and, for a nested module, its last segment (`a.b.c.f` and
+    /// `c.f`). A last segment shared with another import names neither.
+    pub fn import_prefixes(&self) -> Vec<(String, String)> {
+        let mut prefixes = Vec::new();
+        let mut last_segments = Vec::new();
+        for import in &self.imports {
+            let path = &import.module_name.name;
+            match &import.alias {
+                Some(alias) => prefixes.push((alias.name.clone(), path.clone())),
+                None => {
+                    prefixes.push((path.clone(), path.clone()));
+                    if let Some((_, last)) = path.rsplit_once('.') {
+                        last_segments.push((last.to_string(), path.clone()));
+                    }
+                }
+            }
+        }
+        let shared = |segment: &str| {
+            prefixes.iter().any(|(prefix, _)| prefix == segment)
+                || last_segments.iter().filter(|(other, _)| other == segment).count() > 1
+        };
+        let unique: Vec<_> = last_segments.iter()
+            .filter(|(segment, _)| !shared(segment))
+            .cloned()
+            .collect();
+        prefixes.extend(unique);
+        prefixes
+    }
+}
+
+impl FunctionReference {
+    /// Reference to a possibly dotted name: `a.b.c.func` names `func` in
+    /// module `a.b.c`
+    pub fn from_path(name: Identifier) -> Self {
+        match name.name.rsplit_once('.') {
+            Some((module, function)) => FunctionReference::Qualified {
+                module: Identifier::new(module.to_string(), name.source_location.clone()),
+                name: Identifier::new(function.to_string(), name.source_location.clone()),
+            },
+            None => FunctionReference::Local { name },
+        }
+    }
+    
+    /// The referenced name, prefixed with its module path if it has one
+    pub fn path(&self) -> Identifier {
+        match self {
+            FunctionReference::Local { name } | FunctionReference::External { name } => name.clone(),
+            FunctionReference::Qualified { module, name } => {
+                Identifier::new(format!("{}.{}", module.name, name.name), name.source_location.clone())
+            }
+        }
+    }
+}
+
 /// Pretty printer for AST nodes
 pub struct ASTPrettyPrinter {
     indent_level: usize,
@@ -1174,11 +1497,7 @@ impl ASTPrettyPrinter {
                 format!("({} + {})", self.print_expression(left), self.print_expression(right))
             }
             Expression::FunctionCall { call, .. } => {
-                let func_name = match &call.function_reference {
-                    FunctionReference::Local { name } => name.name.clone(),
-                    FunctionReference::Qualified { module, name } => format!("{}.{}", module.name, name.name),
-                    FunctionReference::External { name } => name.name.clone(),
-                };
+                let func_name = call.function_reference.path().name;
                 let args: Vec<String> = call.arguments.iter().map(|arg| {
                     format!("{}: {}", arg.parameter_name.name, self.print_expression(&arg.value))
                 }).collect();
@@ -1235,6 +1554,7 @@ mod tests {
         let loc = SourceLocation::new("test.aether".to_string(), 1, 1, 0);
         let expr = Expression::IntegerLiteral {
             value: 42,
+            suffix: None,
             source_location: loc,
         };
         
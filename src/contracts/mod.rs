@@ -581,6 +581,8 @@ mod tests {
             throws_exceptions: Vec::new(),
             thread_safe: None,
             may_block: None,
+            pure: None,
+            optimization_hints: vec![],
         };
 
         let code = validator.generate_runtime_assertions(&metadata, "test_function");
@@ -403,12 +403,7 @@ impl BreakpointManager {
         line: u32,
         column: Option<u32>,
     ) -> Result<u32, SemanticError> {
-        let location = SourceLocation {
-            file,
-            line: line as usize,
-            column: column.unwrap_or(0) as usize,
-            offset: 0,
-        };
+        let location = SourceLocation::new(file, line as usize, column.unwrap_or(0) as usize, 0);
         
         self.create_breakpoint(BreakpointType::Line, location, None)
     }
@@ -434,12 +429,12 @@ impl BreakpointManager {
         function_name: String,
         on_entry: bool,
     ) -> Result<u32, SemanticError> {
-        let location = SourceLocation {
-            file: "".to_string(), // Function breakpoints may not have specific file
-            line: 0,
-            column: 0,
-            offset: 0,
-        };
+        let location = SourceLocation::new(
+            "".to_string(), // Function breakpoints may not have specific file
+            0,
+            0,
+            0,
+        );
         
         let breakpoint_type = if on_entry {
             BreakpointType::FunctionEntry
@@ -755,12 +750,7 @@ mod tests {
     fn test_breakpoint_creation() {
         let mut manager = BreakpointManager::new();
         
-        let location = SourceLocation {
-            file: "test.aether".to_string(),
-            line: 10,
-            column: 5,
-            offset: 0,
-        };
+        let location = SourceLocation::new("test.aether".to_string(), 10, 5, 0);
         
         let id = manager.create_breakpoint(BreakpointType::Line, location.clone(), None).unwrap();
         assert!(id > 0);
@@ -786,12 +776,7 @@ mod tests {
     fn test_conditional_breakpoint() {
         let mut manager = BreakpointManager::new();
         
-        let location = SourceLocation {
-            file: "test.aether".to_string(),
-            line: 20,
-            column: 0,
-            offset: 0,
-        };
+        let location = SourceLocation::new("test.aether".to_string(), 20, 0, 0);
         
         let id = manager.create_conditional_breakpoint(location, "x == 42".to_string()).unwrap();
         let breakpoint = manager.get_breakpoint(id).unwrap();
@@ -466,12 +466,7 @@ impl DebuggerInterface {
             DebugCommand::SetBreakpoint { file, line } => {
                 DebugResponse::BreakpointSet {
                     id: 1,
-                    location: SourceLocation {
-                        file: file.clone(),
-                        line: *line as usize,
-                        column: 1,
-                        offset: 0,
-                    },
+                    location: SourceLocation::new(file.clone(), *line as usize, 1, 0),
                 }
             }
             DebugCommand::ExamineVariable { name } => {
@@ -493,12 +488,7 @@ impl DebuggerInterface {
     pub fn set_breakpoint(&mut self, file: &str, line: u32) -> Result<(), SemanticError> {
         let breakpoint = Breakpoint {
             id: self.breakpoints.next_id,
-            location: SourceLocation {
-                file: file.to_string(),
-                line: line as usize,
-                column: 1,
-                offset: 0,
-            },
+            location: SourceLocation::new(file.to_string(), line as usize, 1, 0),
             enabled: true,
             condition: None,
             hit_count: 0,
@@ -729,12 +719,7 @@ mod tests {
         let frame = StackFrame {
             index: 0,
             function_name: "main".to_string(),
-            location: SourceLocation {
-                file: "test.aether".to_string(),
-                line: 10,
-                column: 5,
-                offset: 0,
-            },
+            location: SourceLocation::new("test.aether".to_string(), 10, 5, 0),
             address: 0x1000,
             locals: vec![],
         };
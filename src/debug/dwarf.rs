@@ -211,7 +211,7 @@ impl DwarfGenerator {
         self.compilation_units.push(comp_unit);
         
         // Generate debug info for all functions
-        for (name, function) in &program.functions {
+        for (name, function) in program.functions_sorted() {
             self.generate_function_debug_info(name, function, backend)?;
         }
         
@@ -384,7 +384,7 @@ impl DwarfGenerator {
         self.add_source_file("main.aether".to_string());
         
         // Generate line entries for each function
-        for (name, function) in &program.functions {
+        for (name, function) in program.functions_sorted() {
             self.generate_function_line_info(name, function)?;
         }
         
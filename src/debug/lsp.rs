@@ -673,12 +673,12 @@ impl LanguageServer {
         if let Some(document) = self.document_manager.documents.get(uri) {
             if let Some(ref semantic_info) = document.semantic_info {
                 // Find symbol at position
-                let location = SourceLocation {
-                    file: uri.to_string(),
-                    line: position.line as usize,
-                    column: position.character as usize,
-                    offset: 0, // We don't have the exact offset from LSP position
-                };
+                let location = SourceLocation::new(
+                    uri.to_string(),
+                    position.line as usize,
+                    position.character as usize,
+                    0, // We don't have the exact offset from LSP position
+                );
                 
                 if let Some(symbol) = semantic_info.symbols.values().find(|s| {
                     s.definition.file == location.file &&
@@ -716,12 +716,12 @@ impl LanguageServer {
         if let Some(document) = self.document_manager.documents.get(uri) {
             if let Some(ref semantic_info) = document.semantic_info {
                 // Find symbol at position and return its definition
-                let location = SourceLocation {
-                    file: uri.to_string(),
-                    line: position.line as usize,
-                    column: position.character as usize,
-                    offset: 0, // We don't have the exact offset from LSP position
-                };
+                let location = SourceLocation::new(
+                    uri.to_string(),
+                    position.line as usize,
+                    position.character as usize,
+                    0, // We don't have the exact offset from LSP position
+                );
                 
                 if let Some(symbol) = semantic_info.symbols.values().find(|s| {
                     s.references.iter().any(|r| {
@@ -890,12 +890,7 @@ mod tests {
             name: "test_var".to_string(),
             kind: SymbolKind::Variable,
             symbol_type: Type::primitive(crate::ast::PrimitiveType::Integer),
-            definition: SourceLocation {
-                file: "test.aether".to_string(),
-                line: 10,
-                column: 5,
-                offset: 0,
-            },
+            definition: SourceLocation::new("test.aether".to_string(), 10, 5, 0),
             references: vec![],
             documentation: Some("Test variable".to_string()),
         };
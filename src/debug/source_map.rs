@@ -194,7 +194,7 @@ impl SourceMapGenerator {
         self.names.clear();
         
         // Process each function
-        for (name, function) in &program.functions {
+        for (name, function) in program.functions_sorted() {
             self.generate_function_mappings(name, function)?;
         }
         
@@ -232,12 +232,12 @@ impl SourceMapGenerator {
                 column: generated_column,
                 address: Some(block_id as u64 * 0x1000), // Placeholder address
             },
-            original: SourceLocation {
-                file: "main.aether".to_string(), // Simplified
-                line: block_id,
-                column: 0,
-                offset: 0,
-            },
+            original: SourceLocation::new(
+                "main.aether".to_string(), // Simplified
+                block_id,
+                0,
+                0,
+            ),
             name: None,
         };
         
@@ -251,12 +251,7 @@ impl SourceMapGenerator {
                     column: (stmt_idx + 1) as u32 * 4,
                     address: Some((block_id as u64 * 0x1000) + (stmt_idx as u64 * 4)),
                 },
-                original: SourceLocation {
-                    file: "main.aether".to_string(),
-                    line: block_id,
-                    column: (stmt_idx + 1),
-                    offset: 0,
-                },
+                original: SourceLocation::new("main.aether".to_string(), block_id, stmt_idx + 1, 0),
                 name: None,
             };
             
@@ -398,12 +393,7 @@ impl AddressMap {
 impl LineMap {
     /// Add line mapping
     pub fn add_mapping(&mut self, source_file: String, source_line: u32, generated: GeneratedLocation) {
-        let source_location = SourceLocation {
-            file: source_file.clone(),
-            line: source_line as usize,
-            column: 0,
-            offset: 0,
-        };
+        let source_location = SourceLocation::new(source_file.clone(), source_line as usize, 0, 0);
         
         self.line_mappings
             .entry((source_file, source_line))
@@ -486,12 +476,7 @@ mod tests {
                 column: 5,
                 address: Some(0x1000),
             },
-            original: SourceLocation {
-                file: "test.aether".to_string(),
-                line: 1,
-                column: 0,
-                offset: 0,
-            },
+            original: SourceLocation::new("test.aether".to_string(), 1, 0, 0),
             name: Some("test_function".to_string()),
         };
         
@@ -504,12 +489,7 @@ mod tests {
     fn test_address_map() {
         let mut address_map = AddressMap::default();
         
-        let source = SourceLocation {
-            file: "test.aether".to_string(),
-            line: 5,
-            column: 10,
-            offset: 0,
-        };
+        let source = SourceLocation::new("test.aether".to_string(), 5, 10, 0);
         
         address_map.add_mapping(0x1000, source.clone());
         
@@ -544,12 +524,7 @@ mod tests {
             name: "test_var".to_string(),
             location_type: VariableLocationType::Stack { offset: -16 },
             range: LocationRange { start: 0x1000, end: 0x2000 },
-            declaration: SourceLocation {
-                file: "test.aether".to_string(),
-                line: 10,
-                column: 5,
-                offset: 0,
-            },
+            declaration: SourceLocation::new("test.aether".to_string(), 10, 5, 0),
         };
         
         var_map.add_variable_location("main".to_string(), var_location);
@@ -603,6 +578,10 @@ mod tests {
             basic_blocks,
             entry_block: block_id,
             return_local: None,
+            is_pure: false,
+            export_symbol: None,
+            calling_convention: CallingConvention::Rust,
+            pass_control: PassControl::default(),
         });
         
         Program {
@@ -608,12 +608,7 @@ mod tests {
         
         let semantic_error = SemanticError::UndefinedSymbol {
             symbol: "foo".to_string(),
-            location: SourceLocation {
-                file: "test.aether".to_string(),
-                line: 10,
-                column: 5,
-                offset: 0,
-            },
+            location: SourceLocation::new("test.aether".to_string(), 10, 5, 0),
         };
         
         let structured = enhancer.enhance_error(&CompilerError::SemanticError(semantic_error));
@@ -630,12 +625,7 @@ mod tests {
         let type_error = TypeError {
             expected: "Float".to_string(),
             found: "Integer".to_string(),
-            location: SourceLocation {
-                file: "math.aether".to_string(),
-                line: 20,
-                column: 10,
-                offset: 0,
-            },
+            location: SourceLocation::new("math.aether".to_string(), 20, 10, 0),
         };
         
         let structured = enhancer.enhance_error(&CompilerError::TypeError(type_error));
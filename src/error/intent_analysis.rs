@@ -434,7 +434,7 @@ impl StatementAnalyzer for ControlFlowAnalyzer {
                 context.has_conditionals = true;
                 context.actions.push("Makes conditional decisions".to_string());
             }
-            Statement::WhileLoop { .. } | Statement::ForEachLoop { .. } => {
+            Statement::WhileLoop { .. } | Statement::WhileMatchLoop { .. } | Statement::ForEachLoop { .. } => {
                 context.has_loops = true;
                 context.actions.push("Iterates over data".to_string());
             }
@@ -539,6 +539,8 @@ mod tests {
                 throws_exceptions: vec![],
                 thread_safe: Some(true),
                 may_block: Some(false),
+                pure: None,
+                optimization_hints: vec![],
             },
             parameters: vec![],
             return_type: Box::new(TypeSpecifier::Primitive {
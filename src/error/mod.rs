@@ -28,12 +28,22 @@ use std::fmt;
 use thiserror::Error;
 
 /// Source location information for error reporting
+///
+/// `line`, `column` and `offset` mark where the construct starts; the
+/// `end_*` fields mark the position just past its last character. A location
+/// created with [`SourceLocation::new`] is a point whose end equals its start.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub struct SourceLocation {
     pub file: String,
     pub line: usize,
     pub column: usize,
     pub offset: usize,
+    #[serde(default)]
+    pub end_line: usize,
+    #[serde(default)]
+    pub end_column: usize,
+    #[serde(default)]
+    pub end_offset: usize,
 }
 
 impl SourceLocation {
@@ -43,16 +53,34 @@ impl SourceLocation {
             line,
             column,
             offset,
+            end_line: line,
+            end_column: column,
+            end_offset: offset,
         }
     }
 
     pub fn unknown() -> Self {
-        Self {
-            file: "<unknown>".to_string(),
-            line: 0,
-            column: 0,
-            offset: 0,
-        }
+        Self::new("<unknown>".to_string(), 0, 0, 0)
+    }
+
+    /// Create a location running from the start of `start` to the end of `end`
+    pub fn span(start: &SourceLocation, end: &SourceLocation) -> Self {
+        start.clone().with_end(end)
+    }
+
+    /// Extend this location so it ends where `end` ends
+    pub fn with_end(mut self, end: &SourceLocation) -> Self {
+        self.end_line = end.end_line;
+        self.end_column = end.end_column;
+        self.end_offset = end.end_offset;
+        self
+    }
+
+    /// Whether `other` lies entirely within this location's range
+    pub fn contains(&self, other: &SourceLocation) -> bool {
+        self.file == other.file
+            && self.offset <= other.offset
+            && other.end_offset <= self.end_offset
     }
 }
 
@@ -80,6 +108,20 @@ impl SourceSpan {
             end: location,
         }
     }
+
+    /// Span covering the full range recorded in a single location
+    pub fn from_location(location: &SourceLocation) -> Self {
+        let end = SourceLocation::new(
+            location.file.clone(),
+            location.end_line,
+            location.end_column,
+            location.end_offset,
+        );
+        Self {
+            start: SourceLocation::new(location.file.clone(), location.line, location.column, location.offset),
+            end,
+        }
+    }
 }
 
 /// Lexical analysis errors
@@ -367,6 +409,17 @@ pub enum SemanticError {
         enum_name: String,
         location: SourceLocation,
     },
+    #[error("Unreachable code after {after} at {location}")]
+    UnreachableCode {
+        after: String,
+        location: SourceLocation,
+    },
+    
+    #[error("Nesting deeper than the limit of {limit} at {location}")]
+    RecursionLimitExceeded {
+        limit: usize,
+        location: SourceLocation,
+    },
 }
 
 impl From<std::io::Error> for SemanticError {
@@ -17,7 +17,7 @@
 //! Provides FFI support for C/C++, Rust, and Go interoperability
 
 use crate::ast::*;
-use crate::types::{Type, TypeChecker};
+use crate::types::{Type, TypeChecker, TypeDefinition};
 use crate::error::SemanticError;
 use std::collections::HashMap;
 use std::fmt;
@@ -193,8 +193,8 @@ impl FFIAnalyzer {
             // Check if type is FFI-compatible
             if !self.is_ffi_compatible(&param_type) {
                 return Err(SemanticError::InvalidFFI {
-                    message: format!("Parameter '{}' has non-FFI-compatible type: {}", 
-                                   param.name.name, param_type),
+                    message: format!("Parameter '{}' of external function '{}' has non-FFI-compatible type {} ({:?} calling convention); pass it through a pointer instead",
+                                   param.name.name, ext_func.name.name, param_type, ext_func.calling_convention),
                     location: param.source_location.clone(),
                 });
             }
@@ -204,7 +204,8 @@ impl FFIAnalyzer {
         let return_type = self.type_checker.borrow().ast_type_to_type(&ext_func.return_type)?;
         if !self.is_ffi_compatible(&return_type) {
             return Err(SemanticError::InvalidFFI {
-                message: format!("Return type is not FFI-compatible: {}", return_type),
+                message: format!("Return type of external function '{}' is not FFI-compatible: {} ({:?} calling convention)",
+                               ext_func.name.name, return_type, ext_func.calling_convention),
                 location: ext_func.source_location.clone(),
             });
         }
@@ -228,13 +229,43 @@ impl FFIAnalyzer {
     }
     
     /// Check if a type is FFI-compatible
+    ///
+    /// Primitives, pointers and structs whose fields are all FFI-compatible
+    /// have a C layout. Maps, owned values, enums and function types do not
+    /// and must be passed through a pointer.
     fn is_ffi_compatible(&self, aether_type: &Type) -> bool {
+        self.is_ffi_compatible_within(aether_type, &mut Vec::new())
+    }
+    
+    /// Check FFI compatibility inside the named types in `visiting`. A struct
+    /// that contains itself by value has no finite layout, so meeting one of
+    /// them again makes the type incompatible.
+    fn is_ffi_compatible_within(&self, aether_type: &Type, visiting: &mut Vec<String>) -> bool {
         match aether_type {
             Type::Primitive(_) => true,
-            Type::Pointer { target_type, .. } => self.is_ffi_compatible(target_type),
-            Type::Array { element_type, .. } => self.is_ffi_compatible(element_type),
+            // A pointer to a struct is an opaque address, so the struct itself
+            // does not need to be FFI-compatible (and may point to itself)
+            Type::Pointer { target_type, .. } => {
+                matches!(target_type.as_ref(), Type::Named { .. }) || self.is_ffi_compatible_within(target_type, visiting)
+            }
+            Type::Array { element_type, .. } => self.is_ffi_compatible_within(element_type, visiting),
+            Type::Named { name, .. } => {
+                if visiting.contains(name) {
+                    return false;
+                }
+                visiting.push(name.clone());
+                let compatible = match self.type_checker.borrow().lookup_type_definition(name) {
+                    Some(TypeDefinition::Struct { fields, .. }) => {
+                        fields.iter().all(|(_, field_type)| self.is_ffi_compatible_within(field_type, visiting))
+                    }
+                    Some(TypeDefinition::Alias { target_type, .. }) => self.is_ffi_compatible_within(target_type, visiting),
+                    Some(TypeDefinition::Enum { .. }) | None => false,
+                };
+                visiting.pop();
+                compatible
+            }
             Type::Function { .. } => false, // Function pointers need special handling
-            Type::Named { .. } => false,    // Named types need special handling
+            Type::Map { .. } | Type::Owned { .. } => false,
             _ => false,
         }
     }
@@ -1364,4 +1395,97 @@ mod tests {
         // Should succeed with ownership info
         assert!(analyzer.analyze_external_function(&ext_func).is_ok());
     }
+    
+    #[test]
+    fn test_map_parameter_rejected() {
+        let type_checker = Rc::new(RefCell::new(TypeChecker::new()));
+        let mut analyzer = FFIAnalyzer::new(type_checker);
+        
+        let mut ext_func = create_test_external_function();
+        ext_func.parameters[1].param_type = Box::new(TypeSpecifier::Map {
+            key_type: Box::new(TypeSpecifier::Primitive {
+                type_name: PrimitiveType::String,
+                source_location: SourceLocation::unknown(),
+            }),
+            value_type: Box::new(TypeSpecifier::Primitive {
+                type_name: PrimitiveType::Integer,
+                source_location: SourceLocation::unknown(),
+            }),
+            source_location: SourceLocation::unknown(),
+        });
+        
+        let message = analyzer.analyze_external_function(&ext_func).unwrap_err().to_string();
+        assert!(message.contains("Parameter 'b'"));
+        assert!(message.contains("Map<String, Integer>"));
+        assert!(analyzer.get_external_functions().is_empty());
+    }
+    
+    #[test]
+    fn test_pointer_and_struct_parameters_accepted() {
+        let type_checker = Rc::new(RefCell::new(TypeChecker::new()));
+        let node_pointer = Type::pointer(Type::named("Node".to_string(), None), true);
+        type_checker.borrow_mut().add_type_definition("Node".to_string(), TypeDefinition::Struct {
+            fields: vec![
+                ("value".to_string(), Type::primitive(PrimitiveType::Integer)),
+                ("next".to_string(), node_pointer),
+            ],
+            source_location: SourceLocation::unknown(),
+        });
+        let mut analyzer = FFIAnalyzer::new(type_checker);
+        
+        // A self-referential struct behind a pointer
+        let mut ext_func = create_test_external_function();
+        ext_func.parameters[0].param_type = Box::new(TypeSpecifier::Pointer {
+            target_type: Box::new(TypeSpecifier::Named {
+                name: Identifier::new("Node".to_string(), SourceLocation::unknown()),
+                source_location: SourceLocation::unknown(),
+            }),
+            is_mutable: true,
+            source_location: SourceLocation::unknown(),
+        });
+        assert!(analyzer.analyze_external_function(&ext_func).is_ok());
+        
+        // The same struct passed by value has a C layout too
+        ext_func.parameters[1].param_type = Box::new(TypeSpecifier::Named {
+            name: Identifier::new("Node".to_string(), SourceLocation::unknown()),
+            source_location: SourceLocation::unknown(),
+        });
+        assert!(analyzer.analyze_external_function(&ext_func).is_ok());
+    }
+    
+    #[test]
+    fn test_struct_containing_itself_rejected() {
+        let type_checker = Rc::new(RefCell::new(TypeChecker::new()));
+        let integer = Type::primitive(PrimitiveType::Integer);
+        for (name, fields) in [
+            ("Loop", vec![("value", integer.clone()), ("next", Type::named("Loop".to_string(), None))]),
+            ("Tree", vec![("root", Type::named("Branch".to_string(), None))]),
+            ("Branch", vec![("value", integer.clone()), ("tree", Type::named("Tree".to_string(), None))]),
+            ("Point", vec![("x", integer.clone()), ("y", integer)]),
+            ("Line", vec![("from", Type::named("Point".to_string(), None)), ("to", Type::named("Point".to_string(), None))]),
+        ] {
+            type_checker.borrow_mut().add_type_definition(name.to_string(), TypeDefinition::Struct {
+                fields: fields.into_iter().map(|(field, ty)| (field.to_string(), ty)).collect(),
+                source_location: SourceLocation::unknown(),
+            });
+        }
+        let mut analyzer = FFIAnalyzer::new(type_checker);
+        let by_value = |name: &str| {
+            let mut ext_func = create_test_external_function();
+            ext_func.parameters[1].param_type = Box::new(TypeSpecifier::Named {
+                name: Identifier::new(name.to_string(), SourceLocation::unknown()),
+                source_location: SourceLocation::unknown(),
+            });
+            ext_func
+        };
+        
+        // Directly or through another struct, the cycle is a diagnostic
+        for name in ["Loop", "Tree"] {
+            let message = analyzer.analyze_external_function(&by_value(name)).unwrap_err().to_string();
+            assert!(message.contains("Parameter 'b'"), "{}", message);
+        }
+        
+        // A struct used twice without a cycle is still compatible
+        assert!(analyzer.analyze_external_function(&by_value("Line")).is_ok());
+    }
 }
\ No newline at end of file
@@ -71,6 +71,16 @@ impl Token {
             lexeme,
         }
     }
+
+    /// The type suffix written on an integer literal, such as `i64` in `100i64`
+    pub fn integer_suffix(&self) -> Option<&str> {
+        match self.token_type {
+            TokenType::Integer(_) => ["i32", "i64"]
+                .into_iter()
+                .find(|suffix| self.lexeme.ends_with(suffix)),
+            _ => None,
+        }
+    }
 }
 
 /// Lexer for AetherScript source code
@@ -116,6 +126,7 @@ impl Lexer {
             "DEFINE_FUNCTION",
             // Enumeration and pattern matching keywords
             "VARIANTS", "VARIANT", "HOLDS", "MATCH_EXPRESSION", "CASE",
+            "INTEGER_RANGE", "INTEGER_RANGE_INCLUSIVE",
             // Type keywords
             "INTEGER", "FLOAT", "STRING", "CHAR", "BOOLEAN", "VOID", "ARRAY_OF_TYPE", 
             "MAP_FROM_TYPE_TO_TYPE", "POINTER_TO",
@@ -124,30 +135,37 @@ impl Lexer {
             // Expression keywords
             "EXPRESSION_ADD", "EXPRESSION_SUBTRACT", "EXPRESSION_MULTIPLY", "EXPRESSION_DIVIDE",
             "EXPRESSION_INTEGER_DIVIDE", "EXPRESSION_MODULO", "EXPRESSION_NEGATE",
+            "EXPRESSION_BITWISE_AND", "EXPRESSION_BITWISE_OR", "EXPRESSION_BITWISE_XOR",
+            "EXPRESSION_SHIFT_LEFT", "EXPRESSION_SHIFT_RIGHT",
             // Predicate keywords
             "PREDICATE_EQUALS", "PREDICATE_NOT_EQUALS", "PREDICATE_LESS_THAN",
             "PREDICATE_LESS_THAN_OR_EQUAL_TO", "PREDICATE_GREATER_THAN", "PREDICATE_GREATER_THAN_OR_EQUAL_TO",
             // Logical keywords
             "LOGICAL_AND", "LOGICAL_OR", "LOGICAL_NOT",
             // String operations
-            "STRING_CONCAT", "STRING_LENGTH", "STRING_CHAR_AT", "SUBSTRING", "STRING_EQUALS", "STRING_CONTAINS",
+            "STRING_CONCAT", "INTERPOLATED_STRING", "STRING_LENGTH", "STRING_CHAR_AT", "SUBSTRING", "STRING_EQUALS", "STRING_CONTAINS",
             // Type conversion
             "CAST_TO_TYPE", "TO_STRING", "TO_INTEGER", "TO_FLOAT",
+            // Type layout
+            "SIZE_OF", "ALIGN_OF",
             // Control flow keywords
             "IF_CONDITION", "THEN_EXECUTE", "ELSE_IF_CONDITION", "ELSE_EXECUTE",
-            "LOOP_WHILE_CONDITION", "LOOP_FOR_EACH_ELEMENT", "LOOP_FIXED_ITERATIONS",
+            "LOOP_WHILE_CONDITION", "LOOP_WHILE_MATCH", "LOOP_FOR_EACH_ELEMENT", "LOOP_FIXED_ITERATIONS",
             "COUNTER", "FROM", "TO", "STEP", "DO",
             "BREAK_LOOP", "CONTINUE_LOOP",
             // Assignment and access keywords
             "ASSIGN", "TARGET_VARIABLE", "SOURCE_EXPRESSION", "GET_FIELD_VALUE",
             "GET_ARRAY_ELEMENT", "SET_ARRAY_ELEMENT", "GET_MAP_VALUE", "SET_MAP_VALUE",
+            "SLICE", "SLICE_INCLUSIVE",
             // Error handling keywords
-            "TRY_EXECUTE", "CATCH_EXCEPTION", "FINALLY_EXECUTE", "THROW_EXCEPTION",
+            "TRY_EXECUTE", "CATCH_EXCEPTION", "FINALLY_EXECUTE", "THROW_EXCEPTION", "PROPAGATE_ERROR", "DEFER",
             // Metadata keywords
             "INTENT", "PRECONDITION", "POSTCONDITION", "INVARIANT", "ALGORITHM_HINT",
-            "PERFORMANCE_EXPECTATION", "COMPLEXITY_EXPECTATION",
+            "PERFORMANCE_EXPECTATION", "COMPLEXITY_EXPECTATION", "PURE", "OPTIMIZE",
             // Pointer operations
             "ADDRESS_OF", "DEREFERENCE", "POINTER_ADD",
+            // Ownership conversions
+            "BORROW", "BORROW_MUT",
             // Mutability
             "mut",
             // FFI keywords
@@ -218,6 +236,19 @@ impl Lexer {
             self.advance();
         }
 
+        // Hexadecimal, binary and octal integers
+        if self.current_char == Some('0') {
+            let radix = match self.peek() {
+                Some('x') | Some('X') => Some(16),
+                Some('b') | Some('B') => Some(2),
+                Some('o') | Some('O') => Some(8),
+                _ => None,
+            };
+            if let Some(radix) = radix {
+                return self.read_radix_integer(number_str, radix, start_location);
+            }
+        }
+
         // Read digits before decimal point
         while let Some(ch) = self.current_char {
             if ch.is_ascii_digit() {
@@ -272,18 +303,79 @@ impl Lexer {
                 }),
             }
         } else {
-            match number_str.parse::<i64>() {
-                Ok(value) => Ok(Token::new(
-                    TokenType::Integer(value),
-                    start_location,
-                    number_str,
-                )),
-                Err(_) => Err(LexerError::InvalidNumber {
-                    value: number_str,
-                    location: start_location,
-                }),
+            let value = number_str.parse::<i64>().ok();
+            let suffix = self.read_integer_suffix(&mut number_str);
+            self.finish_integer(value, &suffix, number_str, start_location)
+        }
+    }
+
+    /// Read an integer written with a `0x`, `0b` or `0o` prefix
+    fn read_radix_integer(&mut self, mut lexeme: String, radix: u32, start_location: SourceLocation) -> Result<Token, LexerError> {
+        let negative = lexeme.starts_with('-');
+        let mut digits = String::new();
+
+        // Prefix
+        for _ in 0..2 {
+            if let Some(ch) = self.current_char {
+                lexeme.push(ch);
+                self.advance();
+            }
+        }
+
+        while let Some(ch) = self.current_char {
+            if ch.is_digit(radix) {
+                digits.push(ch);
+                lexeme.push(ch);
+                self.advance();
+            } else if ch == '_' {
+                lexeme.push(ch);
+                self.advance();
+            } else {
+                break;
             }
         }
+
+        let suffix = self.read_integer_suffix(&mut lexeme);
+        let value = i64::from_str_radix(&digits, radix)
+            .ok()
+            .map(|value| if negative { -value } else { value });
+        self.finish_integer(value, &suffix, lexeme, start_location)
+    }
+
+    /// Read a type suffix such as `i32` directly after an integer's digits
+    fn read_integer_suffix(&mut self, lexeme: &mut String) -> String {
+        let mut suffix = String::new();
+        while let Some(ch) = self.current_char {
+            if ch.is_ascii_alphanumeric() {
+                suffix.push(ch);
+                lexeme.push(ch);
+                self.advance();
+            } else {
+                break;
+            }
+        }
+        suffix
+    }
+
+    /// Check an integer's suffix and range, and build its token
+    fn finish_integer(&self, value: Option<i64>, suffix: &str, lexeme: String, start_location: SourceLocation) -> Result<Token, LexerError> {
+        let value = value.filter(|value| match suffix {
+            "" | "i64" => true,
+            "i32" => i32::try_from(*value).is_ok(),
+            _ => false,
+        });
+
+        match value {
+            Some(value) => Ok(Token::new(
+                TokenType::Integer(value),
+                start_location,
+                lexeme,
+            )),
+            None => Err(LexerError::InvalidNumber {
+                value: lexeme,
+                location: start_location,
+            }),
+        }
     }
 
     /// Read a string literal
@@ -310,39 +402,8 @@ impl Lexer {
             } else if ch == '\\' {
                 // Handle escape sequences
                 self.advance();
-                match self.current_char {
-                    Some('n') => {
-                        string_value.push('\n');
-                        lexeme.push('n');
-                    }
-                    Some('t') => {
-                        string_value.push('\t');
-                        lexeme.push('t');
-                    }
-                    Some('r') => {
-                        string_value.push('\r');
-                        lexeme.push('r');
-                    }
-                    Some('\\') => {
-                        string_value.push('\\');
-                        lexeme.push('\\');
-                    }
-                    Some('"') => {
-                        string_value.push('"');
-                        lexeme.push('"');
-                    }
-                    Some(other) => {
-                        return Err(LexerError::InvalidEscapeSequence {
-                            sequence: other.to_string(),
-                            location: self.current_location(),
-                        });
-                    }
-                    None => {
-                        return Err(LexerError::UnterminatedString {
-                            location: start_location,
-                        });
-                    }
-                }
+                let decoded = self.read_escape_sequence('"', &mut lexeme, &start_location)?;
+                string_value.push(decoded);
                 self.advance();
             } else if ch == '\n' || ch == '\r' {
                 return Err(LexerError::UnterminatedString {
@@ -359,6 +420,63 @@ impl Lexer {
         })
     }
 
+    /// Decode the escape sequence following a backslash.
+    ///
+    /// Expects the current character to be the first one after the backslash
+    /// and leaves it on the last character of the sequence. Supports `\n`,
+    /// `\t`, `\r`, `\0`, `\\`, the enclosing quote and `\u{XXXX}`.
+    fn read_escape_sequence(
+        &mut self,
+        quote: char,
+        lexeme: &mut String,
+        start_location: &SourceLocation,
+    ) -> Result<char, LexerError> {
+        let escape_location = self.current_location();
+        let ch = self.current_char.ok_or_else(|| LexerError::UnterminatedString {
+            location: start_location.clone(),
+        })?;
+        lexeme.push(ch);
+        
+        match ch {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '0' => Ok('\0'),
+            '\\' => Ok('\\'),
+            'u' => {
+                let mut sequence = String::from("u");
+                self.advance();
+                if self.current_char == Some('{') {
+                    sequence.push('{');
+                    lexeme.push('{');
+                    self.advance();
+                    while let Some(digit) = self.current_char {
+                        sequence.push(digit);
+                        lexeme.push(digit);
+                        if digit == '}' || !digit.is_ascii_hexdigit() {
+                            break;
+                        }
+                        self.advance();
+                    }
+                }
+                
+                let hex = sequence.strip_prefix("u{").and_then(|rest| rest.strip_suffix('}'));
+                hex.filter(|hex| !hex.is_empty() && hex.len() <= 6)
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .and_then(char::from_u32)
+                    .ok_or(LexerError::InvalidEscapeSequence {
+                        sequence,
+                        location: escape_location,
+                    })
+            }
+            other if other == quote => Ok(other),
+            other => Err(LexerError::InvalidEscapeSequence {
+                sequence: other.to_string(),
+                location: escape_location,
+            }),
+        }
+    }
+
     /// Read an identifier or keyword
     fn read_identifier(&mut self) -> Token {
         let start_location = self.current_location();
@@ -449,7 +567,7 @@ impl Lexer {
                 self.advance();
                 
                 // Determine if this is a character literal or quoted identifier
-                if content.len() == 1 {
+                if content.chars().count() == 1 {
                     // Single character - treat as character literal
                     let character = content.chars().next().unwrap();
                     return Ok(Token::new(
@@ -471,39 +589,8 @@ impl Lexer {
                 // Handle escape sequences for character literals
                 lexeme.push(ch);
                 self.advance();
-                match self.current_char {
-                    Some('n') => {
-                        content.push('\n');
-                        lexeme.push('n');
-                    }
-                    Some('t') => {
-                        content.push('\t');
-                        lexeme.push('t');
-                    }
-                    Some('r') => {
-                        content.push('\r');
-                        lexeme.push('r');
-                    }
-                    Some('\\') => {
-                        content.push('\\');
-                        lexeme.push('\\');
-                    }
-                    Some('\'') => {
-                        content.push('\'');
-                        lexeme.push('\'');
-                    }
-                    Some(other) => {
-                        return Err(LexerError::InvalidEscapeSequence {
-                            sequence: other.to_string(),
-                            location: self.current_location(),
-                        });
-                    }
-                    None => {
-                        return Err(LexerError::UnterminatedString {
-                            location: start_location,
-                        });
-                    }
-                }
+                let decoded = self.read_escape_sequence('\'', &mut lexeme, &start_location)?;
+                content.push(decoded);
                 self.advance();
             } else if ch == '\n' || ch == '\r' {
                 return Err(LexerError::UnterminatedString {
@@ -615,7 +702,9 @@ impl Lexer {
         let mut tokens = Vec::new();
 
         loop {
-            let token = self.next_token()?;
+            let mut token = self.next_token()?;
+            // Tokens end where the lexer stopped reading them
+            token.location = token.location.with_end(&self.current_location());
             let is_eof = matches!(token.token_type, TokenType::Eof);
             tokens.push(token);
 
@@ -674,6 +763,26 @@ mod tests {
         assert!(matches!(tokens[5].token_type, TokenType::Float(f) if (f - 2E-3).abs() < f64::EPSILON));
     }
 
+    #[test]
+    fn test_integer_bases_and_suffixes() {
+        let mut lexer = Lexer::new("0xFF 0b1010 0o17 -0x10 1000i64 0xFFi32 7", "test.aether".to_string());
+        let tokens = lexer.tokenize().unwrap();
+
+        assert!(matches!(tokens[0].token_type, TokenType::Integer(255)));
+        assert!(matches!(tokens[1].token_type, TokenType::Integer(10)));
+        assert!(matches!(tokens[2].token_type, TokenType::Integer(15)));
+        assert!(matches!(tokens[3].token_type, TokenType::Integer(-16)));
+        assert_eq!(tokens[0].lexeme, "0xFF");
+        assert_eq!(tokens[4].integer_suffix(), Some("i64"));
+        assert_eq!(tokens[5].integer_suffix(), Some("i32"));
+        assert_eq!(tokens[6].integer_suffix(), None);
+
+        for invalid in ["255u8", "0x", "3000000000i32", "0b102"] {
+            let mut lexer = Lexer::new(invalid, "test.aether".to_string());
+            assert!(matches!(lexer.tokenize(), Err(LexerError::InvalidNumber { .. })), "{}", invalid);
+        }
+    }
+
     #[test]
     fn test_strings() {
         let mut lexer = Lexer::new(r#""hello" "world\n" "test\"quote""#, "test.aether".to_string());
@@ -684,6 +793,21 @@ mod tests {
         assert!(matches!(tokens[2].token_type, TokenType::String(ref s) if s == "test\"quote"));
     }
 
+    #[test]
+    fn test_unicode_escapes() {
+        let mut lexer = Lexer::new(r#""line\u{000A}" '\u{e9}' "nul\0""#, "test.aether".to_string());
+        let tokens = lexer.tokenize().unwrap();
+
+        assert!(matches!(tokens[0].token_type, TokenType::String(ref s) if s == "line\n"));
+        assert!(matches!(tokens[1].token_type, TokenType::Character('\u{e9}')));
+        assert!(matches!(tokens[2].token_type, TokenType::String(ref s) if s == "nul\0"));
+
+        for invalid in [r#""\u{110000}""#, r#""\u{}""#, r#""\u0041""#] {
+            let mut lexer = Lexer::new(invalid, "test.aether".to_string());
+            assert!(matches!(lexer.tokenize(), Err(LexerError::InvalidEscapeSequence { .. })));
+        }
+    }
+
     #[test]
     fn test_identifiers_and_keywords() {
         let mut lexer = Lexer::new("DEFINE_FUNCTION my_var TRUE FALSE NULL_VALUE", "test.aether".to_string());
@@ -538,6 +538,18 @@ impl<'ctx> CodeGenerator<'ctx> {
                     .map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?;
             }
             
+            Terminator::CondBranch { cond, true_block, false_block } => {
+                let cond_value = self.generate_operand(cond)?.into_int_value();
+                let is_true = self.builder.build_int_compare(
+                    inkwell::IntPredicate::NE,
+                    cond_value,
+                    cond_value.get_type().const_zero(),
+                    "is_true",
+                ).map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?;
+                self.builder.build_conditional_branch(is_true, self.basic_blocks[true_block], self.basic_blocks[false_block])
+                    .map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?;
+            }
+            
             Terminator::Unreachable => {
                 self.builder.build_unreachable()
                     .map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?;
@@ -28,6 +28,7 @@ use inkwell::module::Module;
 use inkwell::targets::{Target, InitializationConfig, TargetMachine, CodeModel, RelocMode, FileType, TargetTriple};
 use inkwell::OptimizationLevel;
 use inkwell::AddressSpace;
+use inkwell::attributes::{Attribute, AttributeLoc};
 use inkwell::builder::Builder;
 use inkwell::values::{FunctionValue, PointerValue, BasicValueEnum};
 use std::path::Path;
@@ -93,6 +94,8 @@ pub struct LLVMBackend<'ctx> {
     target_machine: Option<TargetMachine>,
     function_declarations: Option<HashMap<String, FunctionValue<'ctx>>>,
     string_globals: HashMap<String, PointerValue<'ctx>>,
+    /// Constant arrays and maps already emitted as static data
+    collection_globals: HashMap<mir::ConstantValue, PointerValue<'ctx>>,
     type_definitions: HashMap<String, crate::types::TypeDefinition>,
 }
 
@@ -107,6 +110,7 @@ impl<'ctx> LLVMBackend<'ctx> {
             target_machine: None,
             function_declarations: None,
             string_globals: HashMap::new(),
+            collection_globals: HashMap::new(),
             type_definitions: HashMap::new(),
         }
     }
@@ -116,6 +120,7 @@ impl<'ctx> LLVMBackend<'ctx> {
         match ty {
             crate::types::Type::Primitive(prim) => match prim {
                 crate::ast::PrimitiveType::Integer => self.context.i32_type().into(),
+                crate::ast::PrimitiveType::Integer64 => self.context.i64_type().into(),
                 crate::ast::PrimitiveType::Float => self.context.f64_type().into(),
                 crate::ast::PrimitiveType::Boolean => self.context.i32_type().into(), // Use i32 for bool
                 crate::ast::PrimitiveType::String => self.context.i8_type().ptr_type(AddressSpace::default()).into(),
@@ -146,8 +151,9 @@ impl<'ctx> LLVMBackend<'ctx> {
                     self.context.i8_type().ptr_type(AddressSpace::default()).into()
                 }
             },
-            crate::types::Type::Pointer { .. } => {
-                // Pointers are represented as i8*
+            crate::types::Type::Pointer { .. }
+            | crate::types::Type::Owned { ownership: crate::types::OwnershipKind::MutableBorrow, .. } => {
+                // Pointers and mutable borrows are represented as i8*
                 self.context.i8_type().ptr_type(AddressSpace::default()).into()
             },
             _ => self.context.i32_type().into(), // Default for complex types
@@ -191,26 +197,6 @@ impl<'ctx> LLVMBackend<'ctx> {
         }
     }
     
-    /// Get the size of a type in bytes
-    fn get_type_size(&self, ty: &crate::types::Type) -> u64 {
-        match ty {
-            crate::types::Type::Primitive(prim) => match prim {
-                crate::ast::PrimitiveType::Integer | crate::ast::PrimitiveType::Integer32 => 4,
-                crate::ast::PrimitiveType::Integer64 => 8,
-                crate::ast::PrimitiveType::Float | crate::ast::PrimitiveType::Float64 => 8,
-                crate::ast::PrimitiveType::Float32 => 4,
-                crate::ast::PrimitiveType::Boolean => 4, // i32
-                crate::ast::PrimitiveType::Char => 1,    // i8
-                crate::ast::PrimitiveType::String => 8,  // pointer
-                _ => 8, // Default to pointer size
-            },
-            crate::types::Type::Array { .. } => 8, // pointer
-            crate::types::Type::Named { .. } => 8, // pointer for structs
-            crate::types::Type::Pointer { .. } => 8, // pointer
-            _ => 8, // Default to pointer size
-        }
-    }
-    
     /// Initialize LLVM targets
     pub fn initialize_targets() {
         Target::initialize_all(&InitializationConfig::default());
@@ -254,7 +240,7 @@ impl<'ctx> LLVMBackend<'ctx> {
         
         // First, create a type converter and define all struct types
         let mut type_converter = types::TypeConverter::new(self.context);
-        for (name, type_def) in &program.type_definitions {
+        for (name, type_def) in program.type_definitions_sorted() {
             if let crate::types::TypeDefinition::Struct { fields, .. } = type_def {
                 eprintln!("Defining LLVM struct type for '{}'", name);
                 type_converter.define_struct_type(name, fields)?;
@@ -268,7 +254,7 @@ impl<'ctx> LLVMBackend<'ctx> {
         self.declare_builtin_functions(&mut function_declarations)?;
         
         // Declare external functions
-        for (name, ext_func) in &program.external_functions {
+        for (name, ext_func) in program.external_functions_sorted() {
             // Skip if already declared (e.g., as a builtin)
             if function_declarations.contains_key(name) {
                 continue;
@@ -304,7 +290,7 @@ impl<'ctx> LLVMBackend<'ctx> {
             function_declarations.insert(name.clone(), llvm_func);
         }
         
-        for (name, function) in &program.functions {
+        for (name, function) in program.functions_sorted() {
             // Special handling for main function
             if name == "main" {
                 // Check if main has argc/argv parameters
@@ -392,6 +378,10 @@ impl<'ctx> LLVMBackend<'ctx> {
                                 // Named types (structs, enums) are passed as pointers
                                 self.context.i8_type().ptr_type(AddressSpace::default()).into()
                             }
+                            crate::types::Type::Pointer { .. }
+                            | crate::types::Type::Owned { ownership: crate::types::OwnershipKind::MutableBorrow, .. } => {
+                                self.context.i8_type().ptr_type(AddressSpace::default()).into()
+                            }
                             _ => {
                                 self.context.i32_type().into()
                             }
@@ -427,7 +417,11 @@ impl<'ctx> LLVMBackend<'ctx> {
                     }
                 };
                 
-                let llvm_func = self.module.add_function(name, fn_type, None);
+                // Exported functions are emitted under their C symbol; calls
+                // within the program still find them by name
+                let symbol = function.export_symbol.as_deref().unwrap_or(name);
+                let llvm_func = self.module.add_function(symbol, fn_type, None);
+                self.add_hint_attributes(llvm_func, &function.pass_control);
                 function_declarations.insert(name.clone(), llvm_func);
             }
         }
@@ -436,7 +430,7 @@ impl<'ctx> LLVMBackend<'ctx> {
         self.function_declarations = Some(function_declarations);
         
         // Second pass: generate function bodies
-        for (name, function) in &program.functions {
+        for (name, function) in program.functions_sorted() {
             eprintln!("Processing MIR function: {}", name);
             if name == "main" && function.parameters.is_empty() {
                 // For parameterless main, we generate it as __aether_main
@@ -451,6 +445,26 @@ impl<'ctx> LLVMBackend<'ctx> {
     
     
     
+    /// Pass a function's inlining and placement hints on to LLVM
+    fn add_hint_attributes(&self, llvm_func: FunctionValue<'ctx>, control: &mir::PassControl) {
+        let mut attributes = Vec::new();
+        if !control.allows_inlining() {
+            attributes.push("noinline");
+        } else if control.inline_always {
+            attributes.push("alwaysinline");
+        }
+        if control.cold {
+            attributes.push("cold");
+        } else if control.hot {
+            attributes.push("hot");
+        }
+        
+        for attribute in attributes {
+            let kind = Attribute::get_named_enum_kind_id(attribute);
+            llvm_func.add_attribute(AttributeLoc::Function, self.context.create_enum_attribute(kind, 0));
+        }
+    }
+    
     /// Generate function body only (assumes function already declared)
     fn generate_function_body_only(&mut self, name: &str, function: &mir::Function) -> Result<(), SemanticError> {
         let llvm_func = self.function_declarations.as_ref()
@@ -545,7 +559,15 @@ impl<'ctx> LLVMBackend<'ctx> {
                     mir::Statement::Assign { place, rvalue, .. } => {
                         let result = self.generate_rvalue(rvalue, &local_allocas, &builder, function)?;
                         if let Some(&alloca) = local_allocas.get(&place.local) {
-                            builder.build_store(alloca, result)
+                            // A write through a reference stores to the address it holds
+                            let target = if place.projection == [mir::PlaceElem::Deref] {
+                                builder.build_load(self.context.i8_type().ptr_type(AddressSpace::default()), alloca, "deref_target")
+                                    .map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?
+                                    .into_pointer_value()
+                            } else {
+                                alloca
+                            };
+                            builder.build_store(target, result)
                                 .map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?;
                         }
                     }
@@ -651,6 +673,24 @@ impl<'ctx> LLVMBackend<'ctx> {
                     }
                 }
                 
+                mir::Terminator::CondBranch { cond, true_block, false_block } => {
+                    let cond_value = match self.generate_operand(cond, &local_allocas, &builder, function)? {
+                        BasicValueEnum::IntValue(v) => v,
+                        _ => return Err(SemanticError::CodeGenError {
+                            message: "Expected integer value for branch condition".to_string()
+                        }),
+                    };
+                    let is_true = builder.build_int_compare(
+                        inkwell::IntPredicate::NE,
+                        cond_value,
+                        cond_value.get_type().const_zero(),
+                        "is_true"
+                    ).map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?;
+                    
+                    builder.build_conditional_branch(is_true, llvm_blocks[true_block], llvm_blocks[false_block])
+                        .map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?;
+                }
+                
                 mir::Terminator::Unreachable => {
                     builder.build_unreachable()
                         .map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?;
@@ -671,11 +711,40 @@ impl<'ctx> LLVMBackend<'ctx> {
                     }
                 }
                 
-                mir::Terminator::Assert { .. } => {
-                    // TODO: Implement assertion checks
-                    return Err(SemanticError::CodeGenError {
-                        message: "Assert terminator not yet implemented".to_string()
+                mir::Terminator::Assert { condition, expected, target, .. } => {
+                    let cond_value = self.generate_operand(condition, &local_allocas, &builder, function)?;
+
+                    let int_value = match cond_value {
+                        BasicValueEnum::IntValue(v) => v,
+                        _ => return Err(SemanticError::CodeGenError {
+                            message: "Expected integer value for assert condition".to_string()
+                        }),
+                    };
+
+                    // Booleans are lowered as i32, so compare against zero
+                    let predicate = if *expected {
+                        inkwell::IntPredicate::NE
+                    } else {
+                        inkwell::IntPredicate::EQ
+                    };
+                    let zero = int_value.get_type().const_zero();
+                    let holds = builder.build_int_compare(predicate, int_value, zero, "assert_cond")
+                        .map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?;
+
+                    let fail_block = self.context.append_basic_block(llvm_func, &format!("bb{}_assert_fail", block_id));
+                    builder.build_conditional_branch(holds, llvm_blocks[target], fail_block)
+                        .map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?;
+
+                    // Failed assertions trap rather than falling through
+                    builder.position_at_end(fail_block);
+                    let trap_fn = self.module.get_function("llvm.trap").unwrap_or_else(|| {
+                        let trap_type = self.context.void_type().fn_type(&[], false);
+                        self.module.add_function("llvm.trap", trap_type, None)
                     });
+                    builder.build_call(trap_fn, &[], "")
+                        .map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?;
+                    builder.build_unreachable()
+                        .map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?;
                 }
                 
                 mir::Terminator::Call { func, args, destination, target, .. } => {
@@ -719,6 +788,26 @@ impl<'ctx> LLVMBackend<'ctx> {
         Ok(())
     }
     
+    /// Allocate stack space in the entry block, so loops do not grow the stack
+    fn build_entry_alloca<T: inkwell::types::BasicType<'ctx>>(
+        &self,
+        builder: &Builder<'ctx>,
+        ty: T,
+        name: &str,
+    ) -> Result<PointerValue<'ctx>, SemanticError> {
+        let entry = builder.get_insert_block()
+            .and_then(|block| block.get_parent())
+            .and_then(|function| function.get_first_basic_block())
+            .ok_or_else(|| SemanticError::CodeGenError { message: "No function to allocate in".to_string() })?;
+        let entry_builder = self.context.create_builder();
+        match entry.get_first_instruction() {
+            Some(instruction) => entry_builder.position_before(&instruction),
+            None => entry_builder.position_at_end(entry),
+        }
+        entry_builder.build_alloca(ty, name)
+            .map_err(|e| SemanticError::CodeGenError { message: e.to_string() })
+    }
+    
     /// Generate code for an rvalue
     fn generate_rvalue(
         &mut self, 
@@ -764,6 +853,36 @@ impl<'ctx> LLVMBackend<'ctx> {
                             .map(|v| v.into())
                             .map_err(|e| SemanticError::CodeGenError { message: e.to_string() })
                     }
+                    // Bitwise operations
+                    (mir::BinOp::BitAnd, BasicValueEnum::IntValue(l), BasicValueEnum::IntValue(r)) => {
+                        builder.build_and(l, r, "and")
+                            .map(|v| v.into())
+                            .map_err(|e| SemanticError::CodeGenError { message: e.to_string() })
+                    }
+                    (mir::BinOp::BitOr, BasicValueEnum::IntValue(l), BasicValueEnum::IntValue(r)) => {
+                        builder.build_or(l, r, "or")
+                            .map(|v| v.into())
+                            .map_err(|e| SemanticError::CodeGenError { message: e.to_string() })
+                    }
+                    (mir::BinOp::BitXor, BasicValueEnum::IntValue(l), BasicValueEnum::IntValue(r)) => {
+                        builder.build_xor(l, r, "xor")
+                            .map(|v| v.into())
+                            .map_err(|e| SemanticError::CodeGenError { message: e.to_string() })
+                    }
+                    (mir::BinOp::Shl, BasicValueEnum::IntValue(l), BasicValueEnum::IntValue(r)) => {
+                        let r = builder.build_int_cast_sign_flag(r, l.get_type(), false, "shamt")
+                            .map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?;
+                        builder.build_left_shift(l, r, "shl")
+                            .map(|v| v.into())
+                            .map_err(|e| SemanticError::CodeGenError { message: e.to_string() })
+                    }
+                    (mir::BinOp::Shr, BasicValueEnum::IntValue(l), BasicValueEnum::IntValue(r)) => {
+                        let r = builder.build_int_cast_sign_flag(r, l.get_type(), false, "shamt")
+                            .map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?;
+                        builder.build_right_shift(l, r, true, "shr")
+                            .map(|v| v.into())
+                            .map_err(|e| SemanticError::CodeGenError { message: e.to_string() })
+                    }
                     // Float operations
                     (mir::BinOp::Add, BasicValueEnum::FloatValue(l), BasicValueEnum::FloatValue(r)) => {
                         builder.build_float_add(l, r, "fadd")
@@ -1003,7 +1122,7 @@ impl<'ctx> LLVMBackend<'ctx> {
                 let mut arg_values = Vec::new();
                 
                 // Special handling for map functions that need pointer arguments
-                if function_name == "map_insert" || function_name == "map_get" {
+                if function_name == "map_insert" || function_name == "map_get" || function_name == "map_contains" {
                     eprintln!("DEBUG: Special handling for map function: {}", function_name);
                     
                     // For map functions, we need to pass pointers to the key and value
@@ -1177,26 +1296,40 @@ impl<'ctx> LLVMBackend<'ctx> {
                 
                 // Check if this is a cast to string (TO_STRING operation)
                 if matches!(ty, crate::types::Type::Primitive(crate::ast::PrimitiveType::String)) {
-                    // Generate call to int_to_string
-                    eprintln!("DEBUG: Generating int_to_string call for cast");
+                    // Generate call to float_to_string or int_to_string
+                    let to_string = if operand_value.is_float_value() { "float_to_string" } else { "int_to_string" };
+                    eprintln!("DEBUG: Generating {} call for cast", to_string);
                     
-                    let int_to_string_func = self.function_declarations.as_ref()
-                        .and_then(|decls| decls.get("int_to_string"))
+                    let to_string_func = self.function_declarations.as_ref()
+                        .and_then(|decls| decls.get(to_string))
                         .copied()
                         .ok_or_else(|| SemanticError::CodeGenError {
-                            message: "int_to_string function not found".to_string()
+                            message: format!("{} function not found", to_string)
                         })?;
                     
-                    let call_result = builder.build_call(int_to_string_func, &[operand_value.into()], "call_int_to_string")
+                    let call_result = builder.build_call(to_string_func, &[operand_value.into()], &format!("call_{}", to_string))
                         .map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?;
                     
                     if let Some(basic_value) = call_result.try_as_basic_value().left() {
                         Ok(basic_value)
                     } else {
                         Err(SemanticError::CodeGenError {
-                            message: "int_to_string returned void".to_string()
+                            message: format!("{} returned void", to_string)
                         })
                     }
+                } else if let (BasicValueEnum::IntValue(int_value), inkwell::types::BasicTypeEnum::IntType(target_type)) =
+                    (operand_value, self.get_basic_type(ty))
+                {
+                    // Resize between integer widths (e.g. Char <-> Integer). Chars are
+                    // unsigned code units, so only wider integers are sign-extended.
+                    if int_value.get_type().get_bit_width() == target_type.get_bit_width() {
+                        Ok(operand_value)
+                    } else {
+                        let is_signed = int_value.get_type().get_bit_width() > 8;
+                        builder.build_int_cast_sign_flag(int_value, target_type, is_signed, "int_cast")
+                            .map(|v| v.into())
+                            .map_err(|e| SemanticError::CodeGenError { message: e.to_string() })
+                    }
                 } else {
                     // For other casts, just pass through for now
                     Ok(operand_value)
@@ -1222,23 +1355,7 @@ impl<'ctx> LLVMBackend<'ctx> {
                         };
                         
                         // Calculate total size and field offsets
-                        let mut field_offsets = Vec::new();
-                        let mut current_offset = 0u64;
-                        let mut struct_size = 0u64;
-                        
-                        for (i, field_type) in field_types.iter().enumerate() {
-                            field_offsets.push(current_offset);
-                            let field_size = self.get_type_size(field_type);
-                            current_offset += field_size;
-                            if i < field_types.len() - 1 {
-                                // Add padding for alignment (simplified - align to field size)
-                                let alignment = field_size.min(8);
-                                if current_offset % alignment != 0 {
-                                    current_offset = (current_offset + alignment - 1) / alignment * alignment;
-                                }
-                            }
-                        }
-                        struct_size = current_offset;
+                        let (field_offsets, struct_size) = crate::types::struct_field_layout(&field_types);
                         
                         // Allocate space for the struct
                         let struct_type = self.context.i8_type().array_type(struct_size as u32);
@@ -1250,7 +1367,7 @@ impl<'ctx> LLVMBackend<'ctx> {
                             let field_value = self.generate_operand(operand, local_allocas, builder, function)?;
                             
                             // Use calculated offset for this field
-                            let offset = field_offsets.get(i).copied().unwrap_or(0);
+                            let offset = field_offsets.get(i).copied().unwrap_or(0) as u64;
                             
                             // Get pointer to field location
                             let indices = vec![
@@ -1297,10 +1414,43 @@ impl<'ctx> LLVMBackend<'ctx> {
                     }
                     
                     mir::AggregateKind::Array(_element_type) => {
-                        // TODO: Implement array aggregate construction
-                        Err(SemanticError::CodeGenError {
-                            message: "Array aggregate construction not yet implemented".to_string()
-                        })
+                        // Same layout as the runtime's arrays, [length: i32][elements: i32...],
+                        // so array_get and array_length work on the result
+                        let i32_type = self.context.i32_type();
+                        let array_type = i32_type.array_type(operands.len() as u32 + 1);
+                        let array_alloca = self.build_entry_alloca(builder, array_type, "array_alloca")?;
+                        
+                        let store_slot = |slot: u64, value: inkwell::values::IntValue<'ctx>| -> Result<(), SemanticError> {
+                            let indices = [i32_type.const_int(0, false), i32_type.const_int(slot, false)];
+                            let slot_ptr = unsafe {
+                                builder.build_in_bounds_gep(array_type, array_alloca, &indices, "array_slot_ptr")
+                            }.map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?;
+                            builder.build_store(slot_ptr, value)
+                                .map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?;
+                            Ok(())
+                        };
+                        
+                        store_slot(0, i32_type.const_int(operands.len() as u64, false))?;
+                        for (i, operand) in operands.iter().enumerate() {
+                            let value = match self.generate_operand(operand, local_allocas, builder, function)? {
+                                BasicValueEnum::IntValue(value) => builder.build_int_cast(value, i32_type, "array_elem")
+                                    .map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?,
+                                other => {
+                                    return Err(SemanticError::CodeGenError {
+                                        message: format!("Array aggregate element must be an integer, got {:?}", other)
+                                    });
+                                }
+                            };
+                            store_slot(i as u64 + 1, value)?;
+                        }
+                        
+                        let array_ptr = builder.build_pointer_cast(
+                            array_alloca,
+                            self.context.i8_type().ptr_type(AddressSpace::default()),
+                            "array_ptr"
+                        ).map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?;
+                        
+                        Ok(array_ptr.into())
                     }
                     
                     mir::AggregateKind::Tuple => {
@@ -1401,8 +1551,14 @@ impl<'ctx> LLVMBackend<'ctx> {
                 if let Some(&alloca) = local_allocas.get(&place.local) {
                     // Handle projections if any
                     if place.projection.is_empty() {
-                        // No projections, just return the alloca pointer
-                        Ok(alloca.into())
+                        // No projections, just return the alloca pointer, as
+                        // the i8* that represents pointers
+                        let pointer = builder.build_pointer_cast(
+                            alloca,
+                            self.context.i8_type().ptr_type(AddressSpace::default()),
+                            "ref_ptr"
+                        ).map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?;
+                        Ok(pointer.into())
                     } else {
                         // Handle projections (field access, array index, etc.)
                         let mut current_ptr = alloca;
@@ -1422,25 +1578,13 @@ impl<'ctx> LLVMBackend<'ctx> {
                                 mir::PlaceElem::Field { field, ty: _ } => {
                                     // For field access, calculate proper offset based on struct definition
                                     let offset = if let Some(local_def) = function.locals.get(&place.local) {
-                                        if let crate::types::Type::Named { name, .. } = &local_def.ty {
+                                        if let crate::types::Type::Named { name, module } = &local_def.ty {
                                             // Look up struct definition
-                                            if let Some(type_def) = self.type_definitions.get(name) {
+                                            if let Some(type_def) = crate::types::find_type_definition(&self.type_definitions, name, module.as_deref()) {
                                                 if let crate::types::TypeDefinition::Struct { fields, .. } = type_def {
                                                     // Calculate offset for the field
-                                                    let mut current_offset = 0u64;
-                                                    for (i, (_, field_ty)) in fields.iter().enumerate() {
-                                                        if i == *field as usize {
-                                                            break;
-                                                        }
-                                                        let field_size = self.get_type_size(field_ty);
-                                                        current_offset += field_size;
-                                                        // Add alignment padding
-                                                        let alignment = field_size.min(8);
-                                                        if current_offset % alignment != 0 {
-                                                            current_offset = (current_offset + alignment - 1) / alignment * alignment;
-                                                        }
-                                                    }
-                                                    current_offset
+                                                    let (offsets, _) = crate::types::struct_field_layout(fields.iter().map(|(_, ty)| ty));
+                                                    offsets.get(*field as usize).copied().unwrap_or(0) as u64
                                                 } else {
                                                     eprintln!("WARNING: {} is not a struct", name);
                                                     (*field * 8) as u64  // Fallback
@@ -1606,6 +1750,81 @@ impl<'ctx> LLVMBackend<'ctx> {
         }
     }
     
+    /// Emit a constant array or map as read-only static data in the runtime's
+    /// layout, so it needs no initialization at run time
+    fn get_or_create_collection_global(&mut self, value: &mir::ConstantValue) -> Result<PointerValue<'ctx>, SemanticError> {
+        if let Some(&global_ptr) = self.collection_globals.get(value) {
+            return Ok(global_ptr);
+        }
+        
+        let i8_ptr_type = self.context.i8_type().ptr_type(AddressSpace::default());
+        let global_name = format!(".const.{}", self.collection_globals.len());
+        let initializer: BasicValueEnum<'ctx> = match value {
+            mir::ConstantValue::Array(elements) => {
+                // Same layout as the runtime's arrays, [length: i32][elements: i32...]
+                let i32_type = self.context.i32_type();
+                let mut slots = vec![i32_type.const_int(elements.len() as u64, false)];
+                for element in elements {
+                    slots.push(i32_type.const_int(Self::static_slot_value(element)? as u64, true));
+                }
+                i32_type.const_array(&slots).into()
+            }
+            mir::ConstantValue::Map(entries) => {
+                // A map with no hash map of its own, only a table of i64 slots:
+                // [count][1 if keys are strings][key, value]...
+                let i64_type = self.context.i64_type();
+                let string_keys = entries.iter().any(|(key, _)| matches!(key, mir::ConstantValue::String(_)));
+                let mut slots = vec![
+                    i64_type.const_int(entries.len() as u64, false),
+                    i64_type.const_int(string_keys as u64, false),
+                ];
+                for (key, entry_value) in entries {
+                    slots.push(match key {
+                        mir::ConstantValue::String(text) => self.get_or_create_string_global(text).const_to_int(i64_type),
+                        other => i64_type.const_int(Self::static_slot_value(other)? as u64, true),
+                    });
+                    slots.push(i64_type.const_int(Self::static_slot_value(entry_value)? as u64, true));
+                }
+                let table_data = i64_type.const_array(&slots);
+                let table = self.module.add_global(table_data.get_type(), Some(AddressSpace::default()), &format!("{}.entries", global_name));
+                table.set_initializer(&table_data);
+                table.set_constant(true);
+                table.set_unnamed_addr(true);
+                
+                self.context.const_struct(&[
+                    i8_ptr_type.const_null().into(),
+                    table.as_pointer_value().const_cast(i8_ptr_type).into(),
+                ], false).into()
+            }
+            other => {
+                return Err(SemanticError::CodeGenError {
+                    message: format!("{:?} is not a constant collection", other)
+                });
+            }
+        };
+        
+        let global = self.module.add_global(initializer.get_type(), Some(AddressSpace::default()), &global_name);
+        global.set_initializer(&initializer);
+        global.set_constant(true);
+        global.set_unnamed_addr(true);
+        
+        let global_ptr = global.as_pointer_value().const_cast(i8_ptr_type);
+        self.collection_globals.insert(value.clone(), global_ptr);
+        Ok(global_ptr)
+    }
+    
+    /// Integer stored in a static collection slot for a scalar constant
+    fn static_slot_value(value: &mir::ConstantValue) -> Result<i64, SemanticError> {
+        match value {
+            mir::ConstantValue::Integer(integer) => Ok(*integer as i64),
+            mir::ConstantValue::Bool(boolean) => Ok(*boolean as i64),
+            mir::ConstantValue::Char(character) => Ok(*character as i64),
+            other => Err(SemanticError::CodeGenError {
+                message: format!("Constant collection elements must be integers, booleans or characters, got {:?}", other)
+            }),
+        }
+    }
+    
     /// Create or get a global string constant
     fn get_or_create_string_global(&mut self, string_value: &str) -> PointerValue<'ctx> {
         // Check if we already have this string
@@ -1653,21 +1872,17 @@ impl<'ctx> LLVMBackend<'ctx> {
         match operand {
             mir::Operand::Copy(place) | mir::Operand::Move(place) => {
                 if let Some(&alloca) = local_allocas.get(&place.local) {
-                    // Handle projections (field access)
+                    // Walk the projections, tracking the address of the
+                    // current place and its type
                     let mut current_ptr = alloca;
-                    
-                    // First load the actual struct pointer if this is a pointer to a struct
-                    let struct_ptr = if place.projection.is_empty() {
-                        current_ptr
-                    } else {
-                        // Load the pointer value since structs are stored as pointers
-                        let loaded_ptr = builder.build_load(
-                            self.context.i8_type().ptr_type(AddressSpace::default()),
-                            current_ptr,
-                            "loaded_struct_ptr"
-                        ).map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?;
-                        loaded_ptr.into_pointer_value()
-                    };
+                    let mut place_type = function.locals.get(&place.local)
+                        .map(|local| local.ty.clone())
+                        .or_else(|| {
+                            // Check if it's a parameter
+                            function.parameters.iter()
+                                .find(|p| p.local_id == place.local)
+                                .map(|p| p.ty.clone())
+                        });
                     
                     for projection in &place.projection {
                         match projection {
@@ -1679,44 +1894,43 @@ impl<'ctx> LLVMBackend<'ctx> {
                                     "deref_ptr"
                                 ).map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?;
                                 current_ptr = loaded_ptr.into_pointer_value();
+                                place_type = match place_type {
+                                    Some(crate::types::Type::Pointer { target_type, .. })
+                                    | Some(crate::types::Type::Owned { ownership: crate::types::OwnershipKind::MutableBorrow, base_type: target_type }) => {
+                                        Some(*target_type)
+                                    }
+                                    _ => None,
+                                };
                             }
                             mir::PlaceElem::Field { field, ty } => {
+                                // Load the actual struct pointer since structs are stored as pointers
+                                let struct_ptr = builder.build_load(
+                                    self.context.i8_type().ptr_type(AddressSpace::default()),
+                                    current_ptr,
+                                    "loaded_struct_ptr"
+                                ).map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?
+                                .into_pointer_value();
+                                
                                 // Calculate field offset based on the containing type
-                                let field_offset = if let Some(local_def) = function.locals.get(&place.local) {
-                                    if let crate::types::Type::Named { name, .. } = &local_def.ty {
-                                        // Look up type definition
-                                        if let Some(type_def) = self.type_definitions.get(name) {
-                                            match type_def {
-                                                crate::types::TypeDefinition::Struct { fields, .. } => {
-                                                    // Calculate struct field offset
-                                                    let mut current_offset = 0u64;
-                                                    for (i, (_, field_ty)) in fields.iter().enumerate() {
-                                                        if i == *field as usize {
-                                                            break;
-                                                        }
-                                                        let field_size = self.get_type_size(field_ty);
-                                                        current_offset += field_size;
-                                                        // Add alignment padding
-                                                        let alignment = field_size.min(8);
-                                                        if current_offset % alignment != 0 {
-                                                            current_offset = (current_offset + alignment - 1) / alignment * alignment;
-                                                        }
-                                                    }
-                                                    current_offset
-                                                }
-                                                crate::types::TypeDefinition::Enum { .. } => {
-                                                    // For enums: field 0 (discriminant) = 0, field 1 (data) = after discriminant
-                                                    if *field == 0 {
-                                                        0
-                                                    } else {
-                                                        // Get the discriminant size for this enum
-                                                        self.get_enum_discriminant_size(name)
-                                                    }
+                                let field_offset = if let Some(crate::types::Type::Named { name, module }) = &place_type {
+                                    // Look up type definition
+                                    if let Some(type_def) = crate::types::find_type_definition(&self.type_definitions, name, module.as_deref()) {
+                                        match type_def {
+                                            crate::types::TypeDefinition::Struct { fields, .. } => {
+                                                // Calculate struct field offset
+                                                let (offsets, _) = crate::types::struct_field_layout(fields.iter().map(|(_, ty)| ty));
+                                                offsets.get(*field as usize).copied().unwrap_or(0) as u64
+                                            }
+                                            crate::types::TypeDefinition::Enum { .. } => {
+                                                // For enums: field 0 (discriminant) = 0, field 1 (data) = after discriminant
+                                                if *field == 0 {
+                                                    0
+                                                } else {
+                                                    // Get the discriminant size for this enum
+                                                    self.get_enum_discriminant_size(name)
                                                 }
-                                                _ => (*field as u64) * 8  // Fallback
                                             }
-                                        } else {
-                                            (*field as u64) * 8  // Fallback
+                                            _ => (*field as u64) * 8  // Fallback
                                         }
                                     } else {
                                         (*field as u64) * 8  // Fallback
@@ -1757,6 +1971,7 @@ impl<'ctx> LLVMBackend<'ctx> {
                                     field_ptr_type,
                                     &format!("field_{}_typed_ptr", field)
                                 ).map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?;
+                                place_type = Some(ty.clone());
                             }
                             _ => {
                                 return Err(SemanticError::CodeGenError {
@@ -1767,25 +1982,9 @@ impl<'ctx> LLVMBackend<'ctx> {
                     }
                     
                     // Load the value from the final pointer
-                    let local_type = if place.projection.is_empty() {
-                        // No projections, get the type of the local
-                        function.locals.get(&place.local)
-                            .map(|local| self.get_basic_type(&local.ty))
-                            .or_else(|| {
-                                // Check if it's a parameter
-                                function.parameters.iter()
-                                    .find(|p| p.local_id == place.local)
-                                    .map(|p| self.get_basic_type(&p.ty))
-                            })
-                            .unwrap_or_else(|| self.context.i32_type().into())
-                    } else {
-                        // With projections, use the type from the last projection
-                        if let Some(mir::PlaceElem::Field { ty, .. }) = place.projection.last() {
-                            self.get_basic_type(ty)
-                        } else {
-                            self.context.i32_type().into()
-                        }
-                    };
+                    let local_type = place_type
+                        .map(|ty| self.get_basic_type(&ty))
+                        .unwrap_or_else(|| self.context.i32_type().into());
                     
                     let value = builder.build_load(local_type, current_ptr, &format!("local_{}", place.local))
                         .map_err(|e| SemanticError::CodeGenError { message: e.to_string() })?;
@@ -1830,10 +2029,11 @@ impl<'ctx> LLVMBackend<'ctx> {
                         Ok(string_ptr.into())
                     }
                     mir::ConstantValue::Null => {
-                        // Null constants need type information
-                        Err(SemanticError::CodeGenError {
-                            message: "Null constants not yet implemented".to_string()
-                        })
+                        // A null pointer is the zero value of its pointer type
+                        Ok(self.get_basic_type(&constant.ty).const_zero())
+                    }
+                    mir::ConstantValue::Array(_) | mir::ConstantValue::Map(_) => {
+                        Ok(self.get_or_create_collection_global(&constant.value)?.into())
                     }
                 }
             }
@@ -2141,6 +2341,18 @@ impl<'ctx> LLVMBackend<'ctx> {
         let array_length_fn = self.module.add_function("array_length", array_length_type, None);
         function_declarations.insert("array_length".to_string(), array_length_fn);
         
+        // array_slice: copies a range of an array into a new array
+        // array_slice(void* array, int start, int length) -> void*
+        let array_slice_type = i8_ptr_type.fn_type(&[i8_ptr_type.into(), i32_type.into(), i32_type.into()], false);
+        let array_slice_fn = self.module.add_function("array_slice", array_slice_type, None);
+        function_declarations.insert("array_slice".to_string(), array_slice_fn);
+        
+        // contract_warn: reports a violated contract without aborting
+        // contract_warn(char* message) -> void
+        let contract_warn_type = void_type.fn_type(&[i8_ptr_type.into()], false);
+        let contract_warn_fn = self.module.add_function("contract_warn", contract_warn_type, None);
+        function_declarations.insert("contract_warn".to_string(), contract_warn_fn);
+        
         // array_set: sets an element in an array
         // array_set(void* array, int index, int value) -> void
         let void_type = self.context.void_type();
@@ -2167,6 +2379,19 @@ impl<'ctx> LLVMBackend<'ctx> {
         let string_char_at_fn = self.module.add_function("string_char_at", string_char_at_type, None);
         function_declarations.insert("string_char_at".to_string(), string_char_at_fn);
         
+        // Code point counterparts of the byte-based string functions, selected
+        // by the lowering's string indexing mode
+        // string_length_chars(char* str) -> int
+        let string_length_chars_fn = self.module.add_function("string_length_chars", string_length_type, None);
+        function_declarations.insert("string_length_chars".to_string(), string_length_chars_fn);
+        // string_char_at_chars(char* str, int index) -> char
+        let string_char_at_chars_fn = self.module.add_function("string_char_at_chars", string_char_at_type, None);
+        function_declarations.insert("string_char_at_chars".to_string(), string_char_at_chars_fn);
+        // string_substring_chars(char* str, int start, int length) -> char*
+        let string_substring_chars_type = i8_ptr_type.fn_type(&[i8_ptr_type.into(), i32_type.into(), i32_type.into()], false);
+        let string_substring_chars_fn = self.module.add_function("string_substring_chars", string_substring_chars_type, None);
+        function_declarations.insert("string_substring_chars".to_string(), string_substring_chars_fn);
+        
         // string_equals: checks if two strings are equal
         // string_equals(char* str1, char* str2) -> int
         let string_equals_type = i32_type.fn_type(&[i8_ptr_type.into(), i8_ptr_type.into()], false);
@@ -2197,6 +2422,12 @@ impl<'ctx> LLVMBackend<'ctx> {
         let int_to_string_fn = self.module.add_function("int_to_string", int_to_string_type, None);
         function_declarations.insert("int_to_string".to_string(), int_to_string_fn);
         
+        // float_to_string: converts float to string
+        // float_to_string(double value) -> char*
+        let float_to_string_type = i8_ptr_type.fn_type(&[self.context.f64_type().into()], false);
+        let float_to_string_fn = self.module.add_function("float_to_string", float_to_string_type, None);
+        function_declarations.insert("float_to_string".to_string(), float_to_string_fn);
+        
         // puts: prints a string to stdout
         // puts(char* str) -> int
         let puts_type = i32_type.fn_type(&[i8_ptr_type.into()], false);
@@ -2388,6 +2619,12 @@ impl<'ctx> LLVMBackend<'ctx> {
         let map_get_fn = self.module.add_function("map_get", map_get_type, None);
         function_declarations.insert("map_get".to_string(), map_get_fn);
         
+        // map_contains: checks whether the map holds a value for a key
+        // map_contains(void* map, void* key) -> int
+        let map_contains_type = i32_type.fn_type(&[i8_ptr_type.into(), i8_ptr_type.into()], false);
+        let map_contains_fn = self.module.add_function("map_contains", map_contains_type, None);
+        function_declarations.insert("map_contains".to_string(), map_contains_fn);
+        
         Ok(())
     }
 }
@@ -2489,6 +2726,115 @@ mod tests {
         assert!(backend.verify().is_ok());
     }
     
+    #[test]
+    fn test_array_aggregate_is_built_in_place() {
+        use crate::ast::PrimitiveType;
+        use crate::types::Type;
+        
+        let integer = |value| mir::Operand::Constant(mir::Constant {
+            ty: Type::primitive(PrimitiveType::Integer),
+            value: mir::ConstantValue::Integer(value),
+        });
+        let mut builder = mir::Builder::new();
+        builder.start_function("literal_length".to_string(), vec![], Type::primitive(PrimitiveType::Integer));
+        let array = builder.new_local(Type::array(Type::primitive(PrimitiveType::Integer), None), false);
+        let length = builder.new_local(Type::primitive(PrimitiveType::Integer), false);
+        builder.push_statement(mir::Statement::Assign {
+            place: mir::Place { local: array, projection: vec![] },
+            rvalue: mir::Rvalue::Aggregate {
+                kind: mir::AggregateKind::Array(Type::primitive(PrimitiveType::Integer)),
+                operands: vec![integer(1), integer(2), integer(3)],
+            },
+            source_info: mir::SourceInfo { span: crate::error::SourceLocation::unknown(), scope: 0 },
+        });
+        builder.push_statement(mir::Statement::Assign {
+            place: mir::Place { local: length, projection: vec![] },
+            rvalue: mir::Rvalue::Call {
+                func: mir::Operand::Constant(mir::Constant {
+                    ty: Type::primitive(PrimitiveType::String),
+                    value: mir::ConstantValue::String("array_length".to_string()),
+                }),
+                args: vec![mir::Operand::Copy(mir::Place { local: array, projection: vec![] })],
+            },
+            source_info: mir::SourceInfo { span: crate::error::SourceLocation::unknown(), scope: 0 },
+        });
+        builder.set_terminator(mir::Terminator::Return);
+        let mut function = builder.finish_function();
+        function.return_local = Some(length);
+        
+        let mut program = Program {
+            functions: HashMap::new(),
+            global_constants: HashMap::new(),
+            external_functions: HashMap::new(),
+            type_definitions: HashMap::new(),
+        };
+        program.functions.insert(function.name.clone(), function);
+        
+        let context = Context::create();
+        let mut backend = LLVMBackend::new(&context, "array_aggregate_test");
+        backend.generate_ir(&program).expect("Codegen should succeed");
+        assert!(backend.verify().is_ok());
+        
+        let ir = backend.get_ir_string();
+        assert!(ir.contains("alloca [4 x i32]"));
+        assert!(!ir.lines().any(|line| line.contains("call") && line.contains("@array_create")));
+    }
+    
+    #[test]
+    fn test_constant_collections_are_static_data() {
+        use crate::ast::PrimitiveType;
+        use crate::types::Type;
+        
+        let integer_type = Type::primitive(PrimitiveType::Integer);
+        let string_type = Type::primitive(PrimitiveType::String);
+        let constant = |ty: Type, value| mir::Operand::Constant(mir::Constant { ty, value });
+        let table = constant(Type::array(integer_type.clone(), None), mir::ConstantValue::Array(vec![
+            mir::ConstantValue::Integer(1),
+            mir::ConstantValue::Integer(2),
+            mir::ConstantValue::Integer(3),
+        ]));
+        let numbers = constant(Type::map(string_type.clone(), integer_type.clone()), mir::ConstantValue::Map(vec![
+            (mir::ConstantValue::String("one".to_string()), mir::ConstantValue::Integer(1)),
+            (mir::ConstantValue::String("two".to_string()), mir::ConstantValue::Integer(2)),
+        ]));
+        
+        let mut builder = mir::Builder::new();
+        builder.start_function("lookup".to_string(), vec![], integer_type.clone());
+        let length = builder.new_local(integer_type.clone(), false);
+        let found = builder.new_local(integer_type.clone(), false);
+        let span = crate::error::SourceLocation::unknown();
+        builder.add_call(mir::Place { local: length, projection: vec![] }, "array_length", vec![table], span.clone());
+        builder.add_call(
+            mir::Place { local: found, projection: vec![] },
+            "map_contains",
+            vec![numbers, constant(string_type, mir::ConstantValue::String("two".to_string()))],
+            span,
+        );
+        builder.set_terminator(mir::Terminator::Return);
+        let mut function = builder.finish_function();
+        function.return_local = Some(length);
+        
+        let mut program = Program {
+            functions: HashMap::new(),
+            global_constants: HashMap::new(),
+            external_functions: HashMap::new(),
+            type_definitions: HashMap::new(),
+        };
+        program.functions.insert(function.name.clone(), function);
+        
+        let context = Context::create();
+        let mut backend = LLVMBackend::new(&context, "constant_collection_test");
+        backend.generate_ir(&program).expect("Codegen should succeed");
+        assert!(backend.verify().is_ok());
+        
+        let ir = backend.get_ir_string();
+        assert!(ir.contains("constant [4 x i32] [i32 3, i32 1, i32 2, i32 3]"), "{}", ir);
+        assert!(ir.contains("constant [6 x i64]"), "{}", ir);
+        for builder_call in ["@array_create", "@array_set", "@map_new", "@map_insert"] {
+            assert!(!ir.lines().any(|line| line.contains("call") && line.contains(builder_call)), "{}", ir);
+        }
+    }
+    
     #[test]
     fn test_target_triple_setting() {
         LLVMBackend::initialize_targets();
@@ -2501,4 +2847,42 @@ mod tests {
         
         assert!(result.is_ok());
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_inlining_hints_become_function_attributes() {
+        use crate::ast::{OptimizationHint, PrimitiveType};
+        use crate::types::Type;
+        
+        let mut program = Program {
+            functions: HashMap::new(),
+            global_constants: HashMap::new(),
+            external_functions: HashMap::new(),
+            type_definitions: HashMap::new(),
+        };
+        for (name, hints) in [
+            ("always", vec![OptimizationHint::InlineAlways, OptimizationHint::Hot]),
+            ("never", vec![OptimizationHint::NoInline, OptimizationHint::Cold]),
+        ] {
+            let mut builder = mir::Builder::new();
+            builder.start_function(name.to_string(), vec![], Type::primitive(PrimitiveType::Void));
+            builder.set_terminator(mir::Terminator::Return);
+            let mut function = builder.finish_function();
+            function.pass_control = mir::PassControl::from_hints(&hints);
+            program.functions.insert(function.name.clone(), function);
+        }
+        
+        let context = Context::create();
+        let mut backend = LLVMBackend::new(&context, "hint_test");
+        backend.generate_ir(&program).expect("Codegen should succeed");
+        
+        let has_attribute = |function: &str, attribute: &str| {
+            let kind = Attribute::get_named_enum_kind_id(attribute);
+            backend.module().get_function(function).unwrap()
+                .get_enum_attribute(AttributeLoc::Function, kind)
+                .is_some()
+        };
+        assert!(has_attribute("always", "alwaysinline") && has_attribute("always", "hot"));
+        assert!(has_attribute("never", "noinline") && has_attribute("never", "cold"));
+        assert!(!has_attribute("always", "noinline") && !has_attribute("never", "alwaysinline"));
+    }
+}
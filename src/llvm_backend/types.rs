@@ -14,7 +14,7 @@
 
 //! Type conversion between MIR types and LLVM types
 
-use crate::types::Type;
+use crate::types::{OwnershipKind, Type};
 use crate::ast::PrimitiveType;
 use crate::error::SemanticError;
 use inkwell::context::Context;
@@ -148,6 +148,12 @@ impl<'ctx> TypeConverter<'ctx> {
                 })
             }
             
+            Type::Owned { ownership: OwnershipKind::MutableBorrow, base_type } => {
+                // A mutable borrow is a reference to its owner
+                let base_llvm_type = self.convert_type(base_type)?;
+                Ok(BasicTypeEnum::PointerType(base_llvm_type.ptr_type(AddressSpace::default())))
+            }
+            
             Type::Owned { base_type, .. } => {
                 // Owned types have the same representation as their base type
                 // The ownership is tracked at compile time, not runtime
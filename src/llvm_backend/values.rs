@@ -67,6 +67,13 @@ impl<'ctx> ValueConverter<'ctx> {
                 let null_ptr = self.context.i8_type().ptr_type(AddressSpace::default()).const_null();
                 BasicValueEnum::PointerValue(null_ptr)
             }
+            
+            ConstantValue::Array(_) | ConstantValue::Map(_) => {
+                // Collections live in static globals, which only the backend can create
+                return Err(SemanticError::CodeGenError {
+                    message: "Constant arrays and maps must be emitted as globals".to_string(),
+                });
+            }
         };
         
         Ok(llvm_value)
@@ -244,6 +244,7 @@ impl MemoryAnalyzer {
             }
             
             Statement::WhileLoop { body, .. } |
+            Statement::WhileMatchLoop { body, .. } |
             Statement::ForEachLoop { body, .. } |
             Statement::FixedIterationLoop { body, .. } => {
                 self.analyze_block(body)?;
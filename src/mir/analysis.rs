@@ -0,0 +1,341 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Queries over lowered functions
+//!
+//! Layout and code generation need to know every type a function touches, not
+//! just the types of its locals: field and element types of aggregates, the
+//! signatures of the functions it calls, and the definitions behind named types.
+//! Tooling such as visualizers and coverage wants the control-flow graph as a
+//! plain edge list.
+
+use super::*;
+use crate::types::{find_type_definition, TypeConstraintInfo, TypeDefinition};
+use std::collections::BTreeSet;
+
+/// Every distinct type referenced by `function`'s parameters, locals and
+/// operations, including the component types of those types and the
+/// signatures of called functions. Named types are resolved through
+/// `program.type_definitions`, so their field, variant and alias target types
+/// are included too.
+pub fn used_types(function: &Function, program: &Program) -> BTreeSet<Type> {
+    let mut collector = TypeCollector {
+        program,
+        types: BTreeSet::new(),
+    };
+
+    collector.add(&function.return_type);
+    for parameter in &function.parameters {
+        collector.add(&parameter.ty);
+    }
+    for local in function.locals.values() {
+        collector.add(&local.ty);
+    }
+    for block in function.basic_blocks.values() {
+        for statement in &block.statements {
+            if let Statement::Assign { place, rvalue, .. } = statement {
+                collector.add_place(place);
+                collector.add_rvalue(rvalue);
+            }
+        }
+        collector.add_terminator(&block.terminator);
+    }
+
+    collector.types
+}
+
+/// The control-flow edges of `function`, one `(from, to)` pair per successor
+/// named by each block's terminator, including cleanup and unwind targets.
+/// Edges are ordered by source block, then in terminator order.
+pub fn cfg_edges(function: &Function) -> Vec<(BasicBlockId, BasicBlockId)> {
+    let mut block_ids: Vec<_> = function.basic_blocks.keys().copied().collect();
+    block_ids.sort_unstable();
+
+    let mut edges = Vec::new();
+    for from in block_ids {
+        let first = edges.len();
+        for to in cfg::successors(&function.basic_blocks[&from]) {
+            // A switch may send several values to the same block
+            if !edges[first..].contains(&(from, to)) {
+                edges.push((from, to));
+            }
+        }
+    }
+    edges
+}
+
+struct TypeCollector<'a> {
+    program: &'a Program,
+    types: BTreeSet<Type>,
+}
+
+impl TypeCollector<'_> {
+    /// Add `ty` and, the first time it is seen, every type it refers to
+    fn add(&mut self, ty: &Type) {
+        if !self.types.insert(ty.clone()) {
+            return;
+        }
+
+        match ty {
+            Type::Primitive(_) | Type::Error => {}
+            Type::Named { name, module } => {
+                match find_type_definition(&self.program.type_definitions, name, module.as_deref()) {
+                    Some(TypeDefinition::Struct { fields, .. }) => {
+                        for (_, field_type) in fields {
+                            self.add(field_type);
+                        }
+                    }
+                    Some(TypeDefinition::Enum { variants, .. }) => {
+                        for variant in variants {
+                            if let Some(associated_type) = &variant.associated_type {
+                                self.add(associated_type);
+                            }
+                        }
+                    }
+                    Some(TypeDefinition::Alias { target_type, .. }) => self.add(target_type),
+                    None => {}
+                }
+            }
+            Type::Array { element_type, .. } => self.add(element_type),
+            Type::Map { key_type, value_type } => {
+                self.add(key_type);
+                self.add(value_type);
+            }
+            Type::Pointer { target_type, .. } => self.add(target_type),
+            Type::Function { parameter_types, return_type } => {
+                for parameter_type in parameter_types {
+                    self.add(parameter_type);
+                }
+                self.add(return_type);
+            }
+            Type::Generic { constraints, .. } => {
+                for constraint in constraints {
+                    if let TypeConstraintInfo::SubtypeBound { parent_type } = constraint {
+                        self.add(parent_type);
+                    }
+                }
+            }
+            Type::GenericInstance { type_arguments, .. } => {
+                for argument in type_arguments {
+                    self.add(argument);
+                }
+            }
+            Type::Variable(variable) => {
+                for constraint in &variable.constraints {
+                    self.add(constraint);
+                }
+            }
+            Type::Owned { base_type, .. } => self.add(base_type),
+        }
+    }
+
+    fn add_place(&mut self, place: &Place) {
+        for elem in &place.projection {
+            if let PlaceElem::Field { ty, .. } = elem {
+                self.add(ty);
+            }
+        }
+    }
+
+    fn add_operand(&mut self, operand: &Operand) {
+        match operand {
+            Operand::Copy(place) | Operand::Move(place) => self.add_place(place),
+            Operand::Constant(constant) => self.add(&constant.ty),
+        }
+    }
+
+    /// Add the parameter and return types of the function `func` names
+    fn add_callee(&mut self, func: &Operand) {
+        let program = self.program;
+        if let Operand::Constant(Constant { value: ConstantValue::String(name), .. }) = func {
+            if let Some(callee) = program.functions.get(name) {
+                for parameter in &callee.parameters {
+                    self.add(&parameter.ty);
+                }
+                self.add(&callee.return_type);
+            } else if let Some(external) = program.external_functions.get(name) {
+                for parameter_type in &external.parameters {
+                    self.add(parameter_type);
+                }
+                self.add(&external.return_type);
+            }
+        }
+    }
+
+    fn add_rvalue(&mut self, rvalue: &Rvalue) {
+        match rvalue {
+            Rvalue::Use(operand) | Rvalue::UnaryOp { operand, .. } => self.add_operand(operand),
+            Rvalue::BinaryOp { left, right, .. } => {
+                self.add_operand(left);
+                self.add_operand(right);
+            }
+            Rvalue::Call { func, args } => {
+                self.add_callee(func);
+                for arg in args {
+                    self.add_operand(arg);
+                }
+            }
+            Rvalue::Aggregate { kind, operands } => {
+                match kind {
+                    AggregateKind::Array(element_type) => self.add(element_type),
+                    AggregateKind::Struct(name, _) | AggregateKind::Enum(name, _) => {
+                        self.add(&Type::named(name.clone(), None));
+                    }
+                    AggregateKind::Tuple => {}
+                }
+                for operand in operands {
+                    self.add_operand(operand);
+                }
+            }
+            Rvalue::Cast { operand, ty, .. } => {
+                self.add_operand(operand);
+                self.add(ty);
+            }
+            Rvalue::Ref { place, .. } | Rvalue::Len(place) | Rvalue::Discriminant(place) => self.add_place(place),
+        }
+    }
+
+    fn add_terminator(&mut self, terminator: &Terminator) {
+        match terminator {
+            Terminator::SwitchInt { discriminant, switch_ty, .. } => {
+                self.add_operand(discriminant);
+                self.add(switch_ty);
+            }
+            Terminator::CondBranch { cond, .. } => self.add_operand(cond),
+            Terminator::Call { func, args, destination, .. } => {
+                self.add_callee(func);
+                for arg in args {
+                    self.add_operand(arg);
+                }
+                self.add_place(destination);
+            }
+            Terminator::Drop { place, .. } => self.add_place(place),
+            Terminator::Assert { condition, .. } => self.add_operand(condition),
+            Terminator::Goto { .. } | Terminator::Return | Terminator::Unreachable => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::PrimitiveType;
+    use crate::error::SourceLocation;
+
+    #[test]
+    fn test_used_types_include_component_types() {
+        let integer = Type::primitive(PrimitiveType::Integer);
+        let float = Type::primitive(PrimitiveType::Float);
+        let string = Type::primitive(PrimitiveType::String);
+        let boolean = Type::primitive(PrimitiveType::Boolean);
+        let point = Type::named("Point".to_string(), None);
+        let points = Type::array(point.clone(), None);
+        let scores = Type::map(string.clone(), boolean.clone());
+
+        let mut program = Program {
+            functions: HashMap::new(),
+            global_constants: HashMap::new(),
+            external_functions: HashMap::new(),
+            type_definitions: HashMap::new(),
+        };
+        program.type_definitions.insert("Point".to_string(), TypeDefinition::Struct {
+            fields: vec![("x".to_string(), float.clone()), ("y".to_string(), float.clone())],
+            source_location: SourceLocation::unknown(),
+        });
+
+        let mut builder = Builder::new();
+        builder.start_function("lookup".to_string(), vec![("key".to_string(), string.clone())], integer.clone());
+        program.functions.insert("lookup".to_string(), builder.finish_function());
+
+        builder.start_function("test".to_string(), vec![("points".to_string(), points.clone())], Type::primitive(PrimitiveType::Void));
+        builder.new_local(scores.clone(), false);
+        let result = builder.new_local(integer.clone(), false);
+        builder.push_statement(Statement::Assign {
+            place: Place {
+                local: result,
+                projection: vec![],
+            },
+            rvalue: Rvalue::Call {
+                func: Operand::Constant(Constant {
+                    ty: Type::primitive(PrimitiveType::String),
+                    value: ConstantValue::String("lookup".to_string()),
+                }),
+                args: vec![],
+            },
+            source_info: SourceInfo {
+                span: SourceLocation::unknown(),
+                scope: 0,
+            },
+        });
+        builder.set_terminator(Terminator::Return);
+        let function = builder.finish_function();
+
+        let types = used_types(&function, &program);
+        for expected in [&point, &points, &scores, &integer, &float, &string, &boolean] {
+            assert!(types.contains(expected), "missing {}", expected);
+        }
+    }
+
+    #[test]
+    fn test_cfg_edges_of_if_statement() {
+        let boolean = Type::primitive(PrimitiveType::Boolean);
+        let integer = Type::primitive(PrimitiveType::Integer);
+        let mut builder = Builder::new();
+        builder.start_function("choose".to_string(), vec![("flag".to_string(), boolean.clone())], integer.clone());
+        let flag = Operand::Copy(Place {
+            local: 0,
+            projection: vec![],
+        });
+        let entry = 0;
+        let then_block = builder.new_block();
+        let checked_block = builder.new_block();
+        let else_block = builder.new_block();
+        let join_block = builder.new_block();
+        let unreachable_block = builder.new_block();
+
+        builder.set_terminator(Terminator::SwitchInt {
+            discriminant: flag.clone(),
+            switch_ty: boolean,
+            targets: SwitchTargets {
+                values: vec![0],
+                targets: vec![else_block],
+                otherwise: then_block,
+            },
+        });
+        builder.switch_to_block(then_block);
+        builder.set_terminator(Terminator::Assert {
+            condition: flag,
+            expected: true,
+            message: AssertMessage::new(AssertKind::Custom("checked".to_string()), &SourceLocation::unknown()),
+            target: checked_block,
+            cleanup: Some(unreachable_block),
+        });
+        builder.switch_to_block(checked_block);
+        builder.set_terminator(Terminator::Goto { target: join_block });
+        builder.switch_to_block(else_block);
+        builder.set_terminator(Terminator::Goto { target: join_block });
+        builder.switch_to_block(join_block);
+        builder.set_terminator(Terminator::Return);
+        let function = builder.finish_function();
+
+        assert_eq!(cfg_edges(&function), vec![
+            (entry, else_block),
+            (entry, then_block),
+            (then_block, checked_block),
+            (then_block, unreachable_block),
+            (checked_block, join_block),
+            (else_block, join_block),
+        ]);
+    }
+}
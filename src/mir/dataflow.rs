@@ -207,7 +207,7 @@ impl DataFlowAnalysis for LivenessAnalysis {
         _location: Location,
     ) -> Self::Fact {
         match term {
-            Terminator::SwitchInt { discriminant, .. } => {
+            Terminator::SwitchInt { discriminant, .. } | Terminator::CondBranch { cond: discriminant, .. } => {
                 self.add_operand_uses(discriminant, &mut fact);
             }
             Terminator::Call { func, args, .. } => {
@@ -21,10 +21,12 @@
 pub mod lowering;
 pub mod dataflow;
 pub mod validation;
+pub mod structural;
+pub mod analysis;
 
 use crate::types::Type;
-use crate::error::SourceLocation;
-use std::collections::HashMap;
+use crate::error::{SemanticError, SourceLocation};
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 
 /// A MIR program consists of multiple functions
@@ -36,8 +38,176 @@ pub struct Program {
     pub type_definitions: HashMap<String, crate::types::TypeDefinition>,
 }
 
+/// Runtime functions whose result depends only on their argument values.
+/// Strings are immutable, so repeated calls may share one result anywhere.
+pub const PURE_RUNTIME_FUNCTIONS: &[&str] = &[
+    "string_length",
+    "string_compare",
+    "string_find",
+    "string_char_at",
+    "string_length_chars",
+    "string_char_at_chars",
+];
+
+/// Runtime functions without side effects that read a collection's
+/// contents. Repeated calls may share one result only while no store or
+/// other call can have changed the collection in between.
+pub const MEMORY_READING_RUNTIME_FUNCTIONS: &[&str] = &[
+    "array_length",
+    "array_get",
+    "map_get",
+    "map_contains",
+];
+
+/// Runtime functions without side effects that return a new allocation on
+/// every call. Pure functions may call them, but calls are never shared.
+pub const ALLOCATING_RUNTIME_FUNCTIONS: &[&str] = &[
+    "string_concat",
+    "string_substring",
+    "string_substring_chars",
+];
+
+/// Entries of a map keyed by name, in name order
+fn sorted_by_name<T>(map: &HashMap<String, T>) -> Vec<(&String, &T)> {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+impl Program {
+    /// Functions in name order. Output that users see, such as emitted code
+    /// and printed MIR, iterates through these accessors rather than the
+    /// maps themselves, so it does not change between runs.
+    pub fn functions_sorted(&self) -> Vec<(&String, &Function)> {
+        sorted_by_name(&self.functions)
+    }
+    
+    /// External functions in name order
+    pub fn external_functions_sorted(&self) -> Vec<(&String, &ExternalFunction)> {
+        sorted_by_name(&self.external_functions)
+    }
+    
+    /// Global constants in name order
+    pub fn global_constants_sorted(&self) -> Vec<(&String, &Constant)> {
+        sorted_by_name(&self.global_constants)
+    }
+    
+    /// Type definitions in name order
+    pub fn type_definitions_sorted(&self) -> Vec<(&String, &crate::types::TypeDefinition)> {
+        sorted_by_name(&self.type_definitions)
+    }
+    
+    /// Names of the functions whose calls may share one result anywhere:
+    /// the pure runtime functions
+    pub fn pure_function_names(&self) -> HashSet<String> {
+        PURE_RUNTIME_FUNCTIONS.iter().map(|name| name.to_string()).collect()
+    }
+    
+    /// Names of the functions whose calls are free of side effects but may
+    /// read memory that stores and other calls change: the functions
+    /// declared `PURE`, which may read the collections they are given, and
+    /// the memory-reading runtime functions
+    pub fn memory_reading_function_names(&self) -> HashSet<String> {
+        self.functions.values()
+            .filter(|function| function.is_pure)
+            .map(|function| function.name.clone())
+            .chain(MEMORY_READING_RUNTIME_FUNCTIONS.iter().map(|name| name.to_string()))
+            .collect()
+    }
+    
+    /// Check that functions declared `PURE` only call other pure functions
+    pub fn validate_purity(&self) -> Result<(), SemanticError> {
+        let pure_functions: HashSet<String> = self.pure_function_names().into_iter()
+            .chain(self.memory_reading_function_names())
+            .chain(ALLOCATING_RUNTIME_FUNCTIONS.iter().map(|name| name.to_string()))
+            .collect();
+        let mut pure: Vec<&Function> = self.functions.values().filter(|function| function.is_pure).collect();
+        pure.sort_by(|a, b| a.name.cmp(&b.name));
+        
+        for function in pure {
+            let mut block_ids: Vec<_> = function.basic_blocks.keys().collect();
+            block_ids.sort();
+            for block_id in block_ids {
+                let block = &function.basic_blocks[block_id];
+                let calls = block.statements.iter()
+                    .filter_map(|statement| match statement {
+                        Statement::Assign { rvalue: Rvalue::Call { func, .. }, source_info, .. } => {
+                            Some((func, source_info.span.clone()))
+                        }
+                        _ => None,
+                    })
+                    .chain(match &block.terminator {
+                        Terminator::Call { func, .. } => Some((func, SourceLocation::unknown())),
+                        _ => None,
+                    });
+                
+                for (func, location) in calls {
+                    let callee = match func {
+                        Operand::Constant(Constant { value: ConstantValue::String(name), .. }) => name.as_str(),
+                        _ => "<indirect>",
+                    };
+                    if !pure_functions.contains(callee) {
+                        return Err(SemanticError::InvalidOperation {
+                            operation: format!("call to '{}'", callee),
+                            reason: format!("pure function '{}' may only call other pure functions", function.name),
+                            location,
+                        });
+                    }
+                }
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Check that the program has a `main` the backend can turn into an
+    /// executable entry point: either `main()` or `main(argc, argv)`,
+    /// returning an integer exit code or nothing.
+    pub fn validate_entry_point(&self) -> Result<(), SemanticError> {
+        let main = self.functions.get("main").ok_or_else(|| SemanticError::UndefinedSymbol {
+            symbol: "main".to_string(),
+            location: SourceLocation::unknown(),
+        })?;
+        
+        if self.external_functions.contains_key("main") {
+            return Err(SemanticError::DuplicateDefinition {
+                symbol: "main".to_string(),
+                location: SourceLocation::unknown(),
+                previous_location: SourceLocation::unknown(),
+            });
+        }
+        
+        let params_ok = match main.parameters.as_slice() {
+            [] => true,
+            [argc, argv] => {
+                matches!(argc.ty, Type::Primitive(crate::ast::PrimitiveType::Integer))
+                    && matches!(argv.ty, Type::Array { .. })
+            }
+            _ => false,
+        };
+        let return_ok = matches!(
+            main.return_type,
+            Type::Primitive(crate::ast::PrimitiveType::Integer | crate::ast::PrimitiveType::Void)
+        );
+        
+        if !params_ok || !return_ok {
+            return Err(SemanticError::TypeMismatch {
+                expected: "main() or main(INTEGER, ARRAY) returning INTEGER or VOID".to_string(),
+                found: format!(
+                    "main({}) returning {}",
+                    main.parameters.iter().map(|p| p.ty.to_string()).collect::<Vec<_>>().join(", "),
+                    main.return_type
+                ),
+                location: SourceLocation::unknown(),
+            });
+        }
+        
+        Ok(())
+    }
+}
+
 /// A MIR function in SSA form
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Function {
     pub name: String,
     pub parameters: Vec<Parameter>,
@@ -46,10 +216,87 @@ pub struct Function {
     pub basic_blocks: HashMap<BasicBlockId, BasicBlock>,
     pub entry_block: BasicBlockId,
     pub return_local: Option<LocalId>,
+    /// Declared `PURE`: free of side effects, so repeated calls with the
+    /// same arguments can share one result
+    pub is_pure: bool,
+    /// Unmangled symbol the function is exported under, so that other
+    /// languages can call it
+    pub export_symbol: Option<String>,
+    /// Calling convention callers of the symbol use
+    pub calling_convention: CallingConvention,
+    /// Optimization passes the function opted out of, and its inlining and
+    /// placement hints
+    pub pass_control: PassControl,
+}
+
+/// Optimization passes a function opted out of, and the inlining and placement
+/// hints it gave, through its metadata
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PassControl {
+    /// Run no optimization passes on the function
+    pub disable_all: bool,
+    /// Never inline the function into its callers
+    pub no_inline: bool,
+    /// Inline the function into every caller regardless of its size
+    pub inline_always: bool,
+    /// The function rarely runs
+    pub cold: bool,
+    /// The function runs often
+    pub hot: bool,
+}
+
+impl PassControl {
+    /// Pass control requested by a function's optimization hints
+    pub fn from_hints(hints: &[crate::ast::OptimizationHint]) -> Self {
+        let mut control = Self::default();
+        for hint in hints {
+            match hint {
+                crate::ast::OptimizationHint::None => control.disable_all = true,
+                crate::ast::OptimizationHint::NoInline => control.no_inline = true,
+                crate::ast::OptimizationHint::InlineAlways => control.inline_always = true,
+                crate::ast::OptimizationHint::Cold => control.cold = true,
+                crate::ast::OptimizationHint::Hot => control.hot = true,
+            }
+        }
+        control
+    }
+    
+    /// Whether the function may be inlined into its callers
+    pub fn allows_inlining(&self) -> bool {
+        !self.disable_all && !self.no_inline
+    }
+    
+    /// Whether the function should be inlined into every caller, whatever
+    /// its size. Opting out of inlining wins over asking for it.
+    pub fn requires_inlining(&self) -> bool {
+        self.inline_always && self.allows_inlining()
+    }
+}
+
+impl Function {
+    /// Source name of the parameter held in `local`, or `None` when the
+    /// local is not a parameter
+    pub fn parameter_name(&self, local: LocalId) -> Option<&str> {
+        self.parameters.iter()
+            .find(|param| param.local_id == local)
+            .map(|param| param.name.as_str())
+    }
+    
+    /// Rename the parameter held in `local`, returning whether it is a
+    /// parameter
+    pub fn set_parameter_name(&mut self, local: LocalId, name: impl Into<String>) -> bool {
+        match self.parameters.iter_mut().find(|param| param.local_id == local) {
+            Some(param) => {
+                param.name = name.into();
+                true
+            }
+            None => false,
+        }
+    }
 }
 
 /// Function parameter
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Parameter {
     pub name: String,
     pub ty: Type,
@@ -57,7 +304,7 @@ pub struct Parameter {
 }
 
 /// Local variable/temporary in SSA form
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Local {
     pub ty: Type,
     pub is_mutable: bool,
@@ -65,14 +312,14 @@ pub struct Local {
 }
 
 /// Source information for debugging
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct SourceInfo {
     pub span: SourceLocation,
     pub scope: ScopeId,
 }
 
 /// A basic block contains a sequence of statements and a terminator
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct BasicBlock {
     pub id: BasicBlockId,
     pub statements: Vec<Statement>,
@@ -80,7 +327,7 @@ pub struct BasicBlock {
 }
 
 /// MIR statements (non-branching operations)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Statement {
     /// Assignment: local = rvalue
     Assign {
@@ -98,7 +345,7 @@ pub enum Statement {
 }
 
 /// Right-hand side of assignments
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Rvalue {
     /// Use of an operand
     Use(Operand),
@@ -191,7 +438,7 @@ pub enum PlaceElem {
 }
 
 /// Block terminators (control flow)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Terminator {
     /// Unconditional jump
     Goto {
@@ -205,6 +452,14 @@ pub enum Terminator {
         targets: SwitchTargets,
     },
     
+    /// Two-way branch on a boolean, the canonical form of a boolean
+    /// `SwitchInt` for backends that prefer an `if`
+    CondBranch {
+        cond: Operand,
+        true_block: BasicBlockId,
+        false_block: BasicBlockId,
+    },
+    
     /// Function return
     Return,
     
@@ -238,23 +493,43 @@ pub enum Terminator {
 }
 
 /// Switch targets for conditional branches
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct SwitchTargets {
     pub values: Vec<u128>,
     pub targets: Vec<BasicBlockId>,
     pub otherwise: BasicBlockId,
 }
 
-/// Assertion messages
-#[derive(Debug, Clone)]
-pub enum AssertMessage {
+/// Assertion message: what a runtime check tests and the source it guards
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssertMessage {
+    pub kind: AssertKind,
+    pub location: SourceLocation,
+}
+
+/// What a runtime check tests
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssertKind {
     BoundsCheck { len: Operand, index: Operand },
     Overflow(BinOp, Operand, Operand),
     DivisionByZero(Operand),
     RemainderByZero(Operand),
+    ContractPre(String),
+    ContractPost(String),
     Custom(String),
 }
 
+/// Broad category of a failed runtime check, for reporting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssertCategory {
+    BoundsCheck,
+    Overflow,
+    DivByZero,
+    ContractPre,
+    ContractPost,
+    Other,
+}
+
 /// Binary operations
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum BinOp {
@@ -297,7 +572,7 @@ pub enum UnOp {
 }
 
 /// Cast kinds
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CastKind {
     /// Numeric cast (int to float, etc.)
     Numeric,
@@ -310,7 +585,7 @@ pub enum CastKind {
 }
 
 /// Aggregate kinds
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum AggregateKind {
     Array(Type),
     Tuple,
@@ -341,6 +616,12 @@ pub enum ConstantValue {
     String(String),
     Char(char),
     Null,
+    /// Array whose elements are all known at compile time, emitted as
+    /// static data
+    Array(Vec<ConstantValue>),
+    /// Map whose entries are all known at compile time, emitted as a static
+    /// lookup table
+    Map(Vec<(ConstantValue, ConstantValue)>),
 }
 
 impl PartialEq for ConstantValue {
@@ -352,6 +633,8 @@ impl PartialEq for ConstantValue {
             (ConstantValue::String(a), ConstantValue::String(b)) => a == b,
             (ConstantValue::Char(a), ConstantValue::Char(b)) => a == b,
             (ConstantValue::Null, ConstantValue::Null) => true,
+            (ConstantValue::Array(a), ConstantValue::Array(b)) => a == b,
+            (ConstantValue::Map(a), ConstantValue::Map(b)) => a == b,
             _ => false,
         }
     }
@@ -386,12 +669,20 @@ impl std::hash::Hash for ConstantValue {
             ConstantValue::Null => {
                 5u8.hash(state);
             }
+            ConstantValue::Array(elements) => {
+                6u8.hash(state);
+                elements.hash(state);
+            }
+            ConstantValue::Map(entries) => {
+                7u8.hash(state);
+                entries.hash(state);
+            }
         }
     }
 }
 
 /// External function declaration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ExternalFunction {
     pub name: String,
     pub parameters: Vec<Type>,
@@ -401,7 +692,7 @@ pub struct ExternalFunction {
 }
 
 /// Calling conventions
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CallingConvention {
     Rust,
     C,
@@ -414,7 +705,204 @@ pub type BasicBlockId = u32;
 pub type FieldIdx = u32;
 pub type ScopeId = u32;
 
+impl Place {
+    /// Rename every local the place mentions, including index locals
+    pub fn map_locals(&mut self, f: &mut dyn FnMut(LocalId) -> LocalId) {
+        self.local = f(self.local);
+        for elem in &mut self.projection {
+            if let PlaceElem::Index(index) = elem {
+                *index = f(*index);
+            }
+        }
+    }
+}
+
+impl Operand {
+    /// Rename every local the operand reads
+    pub fn map_locals(&mut self, f: &mut dyn FnMut(LocalId) -> LocalId) {
+        match self {
+            Operand::Copy(place) | Operand::Move(place) => place.map_locals(f),
+            Operand::Constant(_) => {}
+        }
+    }
+}
+
+impl Rvalue {
+    /// Rename every local the rvalue reads
+    pub fn map_locals(&mut self, f: &mut dyn FnMut(LocalId) -> LocalId) {
+        match self {
+            Rvalue::Use(operand)
+            | Rvalue::UnaryOp { operand, .. }
+            | Rvalue::Cast { operand, .. } => operand.map_locals(f),
+            Rvalue::BinaryOp { left, right, .. } => {
+                left.map_locals(f);
+                right.map_locals(f);
+            }
+            Rvalue::Call { func, args } => {
+                func.map_locals(f);
+                for arg in args {
+                    arg.map_locals(f);
+                }
+            }
+            Rvalue::Aggregate { operands, .. } => {
+                for operand in operands {
+                    operand.map_locals(f);
+                }
+            }
+            Rvalue::Ref { place, .. }
+            | Rvalue::Len(place)
+            | Rvalue::Discriminant(place) => place.map_locals(f),
+        }
+    }
+}
+
+impl Statement {
+    /// Rename every local the statement reads or writes
+    pub fn map_locals(&mut self, f: &mut dyn FnMut(LocalId) -> LocalId) {
+        match self {
+            Statement::Assign { place, rvalue, .. } => {
+                place.map_locals(f);
+                rvalue.map_locals(f);
+            }
+            Statement::StorageLive(local) | Statement::StorageDead(local) => *local = f(*local),
+            Statement::Nop => {}
+        }
+    }
+}
+
+impl AssertMessage {
+    pub fn new(kind: AssertKind, location: &SourceLocation) -> Self {
+        Self {
+            kind,
+            location: location.clone(),
+        }
+    }
+    
+    /// Category the runtime reports a failure of this check under
+    pub fn category(&self) -> AssertCategory {
+        match self.kind {
+            AssertKind::BoundsCheck { .. } => AssertCategory::BoundsCheck,
+            AssertKind::Overflow(..) => AssertCategory::Overflow,
+            AssertKind::DivisionByZero(_) | AssertKind::RemainderByZero(_) => AssertCategory::DivByZero,
+            AssertKind::ContractPre(_) => AssertCategory::ContractPre,
+            AssertKind::ContractPost(_) => AssertCategory::ContractPost,
+            AssertKind::Custom(_) => AssertCategory::Other,
+        }
+    }
+    
+    /// Rename every local the message reports
+    pub fn map_locals(&mut self, f: &mut dyn FnMut(LocalId) -> LocalId) {
+        match &mut self.kind {
+            AssertKind::BoundsCheck { len, index } => {
+                len.map_locals(f);
+                index.map_locals(f);
+            }
+            AssertKind::Overflow(_, left, right) => {
+                left.map_locals(f);
+                right.map_locals(f);
+            }
+            AssertKind::DivisionByZero(operand)
+            | AssertKind::RemainderByZero(operand) => operand.map_locals(f),
+            AssertKind::ContractPre(_) | AssertKind::ContractPost(_) | AssertKind::Custom(_) => {}
+        }
+    }
+}
+
+impl fmt::Display for AssertMessage {
+    /// `file:line:column: what failed`, as the runtime reports it. Contract
+    /// messages are written as lowering built them, since whether they name
+    /// their location is configurable.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            AssertKind::BoundsCheck { .. } => write!(f, "{}: index out of bounds", self.location),
+            AssertKind::Overflow(op, ..) => write!(f, "{}: arithmetic overflow in {:?}", self.location, op),
+            AssertKind::DivisionByZero(_) => write!(f, "{}: division by zero", self.location),
+            AssertKind::RemainderByZero(_) => write!(f, "{}: remainder by zero", self.location),
+            AssertKind::ContractPre(message) | AssertKind::ContractPost(message) => write!(f, "{}", message),
+            AssertKind::Custom(message) => write!(f, "{}: {}", self.location, message),
+        }
+    }
+}
+
+impl Terminator {
+    /// Rename every local the terminator reads or writes
+    pub fn map_locals(&mut self, f: &mut dyn FnMut(LocalId) -> LocalId) {
+        match self {
+            Terminator::Goto { .. } | Terminator::Return | Terminator::Unreachable => {}
+            Terminator::SwitchInt { discriminant, .. } => discriminant.map_locals(f),
+            Terminator::CondBranch { cond, .. } => cond.map_locals(f),
+            Terminator::Call { func, args, destination, .. } => {
+                func.map_locals(f);
+                for arg in args {
+                    arg.map_locals(f);
+                }
+                destination.map_locals(f);
+            }
+            Terminator::Drop { place, .. } => place.map_locals(f),
+            Terminator::Assert { condition, message, .. } => {
+                condition.map_locals(f);
+                message.map_locals(f);
+            }
+        }
+    }
+    
+    /// The `CondBranch` equivalent of a `SwitchInt` that tests a boolean
+    /// against a single value, or `None` for any other terminator
+    pub fn as_cond_branch(&self) -> Option<Terminator> {
+        let Terminator::SwitchInt { discriminant, switch_ty, targets } = self else {
+            return None;
+        };
+        if *switch_ty != Type::primitive(crate::ast::PrimitiveType::Boolean) {
+            return None;
+        }
+        let (true_block, false_block) = match (targets.values.as_slice(), targets.targets.as_slice()) {
+            ([1], [target]) => (*target, targets.otherwise),
+            ([0], [target]) => (targets.otherwise, *target),
+            _ => return None,
+        };
+        Some(Terminator::CondBranch {
+            cond: discriminant.clone(),
+            true_block,
+            false_block,
+        })
+    }
+    
+    /// Rename every block the terminator can jump to
+    pub fn map_blocks(&mut self, f: &mut dyn FnMut(BasicBlockId) -> BasicBlockId) {
+        match self {
+            Terminator::Goto { target } => *target = f(*target),
+            Terminator::SwitchInt { targets, .. } => {
+                for target in &mut targets.targets {
+                    *target = f(*target);
+                }
+                targets.otherwise = f(targets.otherwise);
+            }
+            Terminator::CondBranch { true_block, false_block, .. } => {
+                *true_block = f(*true_block);
+                *false_block = f(*false_block);
+            }
+            Terminator::Return | Terminator::Unreachable => {}
+            Terminator::Call { target, cleanup, .. } => {
+                for block in target.iter_mut().chain(cleanup.iter_mut()) {
+                    *block = f(*block);
+                }
+            }
+            Terminator::Drop { target, unwind: other, .. }
+            | Terminator::Assert { target, cleanup: other, .. } => {
+                *target = f(*target);
+                if let Some(block) = other {
+                    *block = f(*block);
+                }
+            }
+        }
+    }
+}
+
 /// MIR builder for constructing MIR from AST
+///
+/// Also usable on its own to hand-construct functions: `start_function`,
+/// then `add_assign`/`add_call` and `terminate` per block, then
+/// `finish_function`.
 pub struct Builder {
     /// Current function being built
     current_function: Option<Function>,
@@ -448,6 +936,7 @@ impl Default for Builder {
 }
 
 impl Builder {
+    /// Create a builder with no function in progress
     pub fn new() -> Self {
         Self {
             current_function: None,
@@ -469,6 +958,10 @@ impl Builder {
             basic_blocks: HashMap::new(),
             entry_block: 0,
             return_local: None,
+            is_pure: false,
+            export_symbol: None,
+            calling_convention: CallingConvention::Rust,
+            pass_control: PassControl::default(),
         };
         
         self.current_function = Some(function);
@@ -553,6 +1046,31 @@ impl Builder {
         }
     }
     
+    /// Append `place = rvalue` to the current block
+    pub fn add_assign(&mut self, place: Place, rvalue: Rvalue, span: SourceLocation) {
+        self.push_statement(Statement::Assign {
+            place,
+            rvalue,
+            source_info: SourceInfo { span, scope: 0 },
+        });
+    }
+    
+    /// Append a call to the function named `func`, storing its result in `dest`
+    pub fn add_call(&mut self, dest: Place, func: &str, args: Vec<Operand>, span: SourceLocation) {
+        let func = Operand::Constant(Constant {
+            ty: Type::primitive(crate::ast::PrimitiveType::String),
+            value: ConstantValue::String(func.to_string()),
+        });
+        self.add_assign(dest, Rvalue::Call { func, args }, span);
+    }
+    
+    /// Terminate the current block, returning its ID so other blocks can target it
+    pub fn terminate(&mut self, terminator: Terminator) -> BasicBlockId {
+        let block_id = self.current_block.expect("No block being built");
+        self.set_terminator(terminator);
+        block_id
+    }
+    
     /// Push a new scope
     pub fn push_scope(&mut self) -> ScopeId {
         let scope_id = self.next_scope_id;
@@ -597,6 +1115,10 @@ pub mod cfg {
                         preds.push(*pred_id);
                     }
                 }
+                Terminator::CondBranch { true_block, false_block, .. }
+                    if *true_block == block_id || *false_block == block_id => {
+                    preds.push(*pred_id);
+                }
                 Terminator::Call { target, cleanup, .. } => {
                     if target.as_ref() == Some(&block_id) || cleanup.as_ref() == Some(&block_id) {
                         preds.push(*pred_id);
@@ -628,6 +1150,7 @@ pub mod cfg {
                 succs.push(targets.otherwise);
                 succs
             }
+            Terminator::CondBranch { true_block, false_block, .. } => vec![*true_block, *false_block],
             Terminator::Return | Terminator::Unreachable => vec![],
             Terminator::Call { target, cleanup, .. } => {
                 let mut succs = Vec::new();
@@ -670,30 +1193,62 @@ impl fmt::Display for Function {
         )?;
         
         // Print locals
-        for (id, local) in &self.locals {
-            writeln!(f, "    let _{}: {};", id, local.ty)?;
+        let mut local_ids: Vec<_> = self.locals.keys().collect();
+        local_ids.sort();
+        for id in local_ids {
+            match self.parameter_name(*id) {
+                Some(name) => writeln!(f, "    let _{}: {}; // {}", id, self.locals[id].ty, name)?,
+                None => writeln!(f, "    let _{}: {};", id, self.locals[id].ty)?,
+            }
         }
         
         writeln!(f)?;
         
         // Print basic blocks
-        for block_id in 0..self.basic_blocks.len() as u32 {
-            if let Some(block) = self.basic_blocks.get(&block_id) {
-                writeln!(f, "  bb{}:", block_id)?;
-                
-                for stmt in &block.statements {
-                    writeln!(f, "    {:?}", stmt)?;
-                }
-                
-                writeln!(f, "    {:?}", block.terminator)?;
-                writeln!(f)?;
+        let mut block_ids: Vec<_> = self.basic_blocks.keys().collect();
+        block_ids.sort();
+        for block_id in block_ids {
+            let block = &self.basic_blocks[block_id];
+            writeln!(f, "  bb{}:", block_id)?;
+            
+            for stmt in &block.statements {
+                writeln!(f, "    {:?}", stmt)?;
             }
+            
+            writeln!(f, "    {:?}", block.terminator)?;
+            writeln!(f)?;
         }
         
         writeln!(f, "}}")
     }
 }
 
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, ty) in self.type_definitions_sorted() {
+            writeln!(f, "type {} = {:?};", name, ty)?;
+        }
+        for (name, constant) in self.global_constants_sorted() {
+            writeln!(f, "const {}: {} = {:?};", name, constant.ty, constant.value)?;
+        }
+        for (name, external) in self.external_functions_sorted() {
+            writeln!(f, "extern fn {}({}) -> {};",
+                name,
+                external.parameters.iter()
+                    .map(|ty| ty.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                external.return_type
+            )?;
+        }
+        for (_, function) in self.functions_sorted() {
+            writeln!(f)?;
+            write!(f, "{}", function)?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -839,4 +1394,132 @@ mod tests {
             _ => panic!("Wrong constant type"),
         }
     }
-}
\ No newline at end of file
+    
+    fn program_with_main(parameters: Vec<(String, Type)>, return_type: Type) -> Program {
+        let mut builder = Builder::new();
+        builder.start_function("main".to_string(), parameters, return_type);
+        builder.set_terminator(Terminator::Return);
+        let main = builder.finish_function();
+        
+        Program {
+            functions: HashMap::from([("main".to_string(), main)]),
+            global_constants: HashMap::new(),
+            external_functions: HashMap::new(),
+            type_definitions: HashMap::new(),
+        }
+    }
+    
+    #[test]
+    fn test_missing_entry_point() {
+        let mut program = program_with_main(vec![], Type::primitive(PrimitiveType::Integer));
+        let helper = program.functions.remove("main").unwrap();
+        program.functions.insert("helper".to_string(), helper);
+        
+        assert!(matches!(
+            program.validate_entry_point(),
+            Err(SemanticError::UndefinedSymbol { ref symbol, .. }) if symbol == "main"
+        ));
+    }
+    
+    #[test]
+    fn test_entry_point_with_wrong_signature() {
+        let struct_param = program_with_main(
+            vec![("config".to_string(), Type::named("Config".to_string(), None))],
+            Type::primitive(PrimitiveType::Integer),
+        );
+        assert!(matches!(struct_param.validate_entry_point(), Err(SemanticError::TypeMismatch { .. })));
+        
+        let string_return = program_with_main(vec![], Type::primitive(PrimitiveType::String));
+        assert!(matches!(string_return.validate_entry_point(), Err(SemanticError::TypeMismatch { .. })));
+    }
+    
+    #[test]
+    fn test_valid_entry_points() {
+        let no_args = program_with_main(vec![], Type::primitive(PrimitiveType::Integer));
+        assert!(no_args.validate_entry_point().is_ok());
+        
+        let argc_argv = program_with_main(
+            vec![
+                ("argc".to_string(), Type::primitive(PrimitiveType::Integer)),
+                ("argv".to_string(), Type::array(Type::primitive(PrimitiveType::String), None)),
+            ],
+            Type::primitive(PrimitiveType::Void),
+        );
+        assert!(argc_argv.validate_entry_point().is_ok());
+    }
+    
+    #[test]
+    fn test_builder_helpers_produce_valid_function() {
+        let mut builder = Builder::new();
+        builder.start_function(
+            "double_and_print".to_string(),
+            vec![("x".to_string(), Type::primitive(PrimitiveType::Integer))],
+            Type::primitive(PrimitiveType::Void),
+        );
+        
+        // bb0: _1 = _0 + _0; goto bb1
+        let doubled = builder.new_local(Type::primitive(PrimitiveType::Integer), false);
+        builder.add_assign(
+            Place { local: doubled, projection: vec![] },
+            Rvalue::BinaryOp {
+                op: BinOp::Add,
+                left: Operand::Copy(Place { local: 0, projection: vec![] }),
+                right: Operand::Copy(Place { local: 0, projection: vec![] }),
+            },
+            SourceLocation::unknown(),
+        );
+        let print_block = builder.new_block();
+        let entry = builder.terminate(Terminator::Goto { target: print_block });
+        
+        // bb1: _2 = print_int(_1); return
+        builder.switch_to_block(print_block);
+        let unit = builder.new_local(Type::primitive(PrimitiveType::Void), false);
+        builder.add_call(
+            Place { local: unit, projection: vec![] },
+            "print_int",
+            vec![Operand::Copy(Place { local: doubled, projection: vec![] })],
+            SourceLocation::unknown(),
+        );
+        builder.terminate(Terminator::Return);
+        
+        let function = builder.finish_function();
+        assert_eq!(entry, function.entry_block);
+        assert_eq!(function.basic_blocks.len(), 2);
+        assert!(matches!(
+            &function.basic_blocks[&print_block].statements[0],
+            Statement::Assign { rvalue: Rvalue::Call { args, .. }, .. } if args.len() == 1
+        ));
+        
+        let mut validator = validation::Validator::new();
+        assert!(validator.validate_function(&function).is_ok());
+    }
+    
+    #[test]
+    fn test_program_prints_identically_across_builds() {
+        // Each build hashes its maps with a fresh seed, so only an output
+        // independent of map order can match
+        let build = || {
+            let mut program = program_with_main(vec![], Type::primitive(PrimitiveType::Integer));
+            for name in ["alpha", "beta", "gamma", "delta", "epsilon"] {
+                let mut builder = Builder::new();
+                builder.start_function(name.to_string(), vec![], Type::primitive(PrimitiveType::Integer));
+                for _ in 0..4 {
+                    builder.new_local(Type::primitive(PrimitiveType::Boolean), false);
+                }
+                builder.set_terminator(Terminator::Return);
+                program.functions.insert(name.to_string(), builder.finish_function());
+                program.global_constants.insert(name.to_uppercase(), Constant {
+                    ty: Type::primitive(PrimitiveType::Integer),
+                    value: ConstantValue::Integer(1),
+                });
+            }
+            program
+        };
+        
+        let first = build().to_string();
+        for _ in 0..8 {
+            assert_eq!(build().to_string(), first);
+        }
+        assert!(first.find("fn alpha").unwrap() < first.find("fn beta").unwrap());
+    }
+}
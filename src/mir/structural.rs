@@ -0,0 +1,291 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Structural comparison of MIR programs
+//!
+//! Golden MIR tests compare a lowering against an expected program. Local and
+//! block IDs depend on allocation order, so both functions are first relabeled
+//! canonically: blocks in depth-first order from the entry block, and locals in
+//! order of first mention (parameters first).
+
+use super::{cfg, BasicBlockId, Function, LocalId, Program};
+use crate::error::SourceLocation;
+use std::collections::{HashMap, HashSet};
+
+/// Differences that `Program::structurally_equal` ignores
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StructuralEqOptions {
+    /// Compare locals after canonical renumbering
+    pub ignore_local_ids: bool,
+    /// Compare blocks after canonical renumbering
+    pub ignore_block_ids: bool,
+    /// Treat all source locations as equal
+    pub ignore_spans: bool,
+}
+
+impl Default for StructuralEqOptions {
+    fn default() -> Self {
+        Self {
+            ignore_local_ids: true,
+            ignore_block_ids: true,
+            ignore_spans: true,
+        }
+    }
+}
+
+impl Program {
+    /// Check whether two programs have the same functions, constants and
+    /// external declarations, up to the differences `options` ignores.
+    /// Type definitions are compared by name only.
+    pub fn structurally_equal(&self, other: &Program, options: StructuralEqOptions) -> bool {
+        let type_names: HashSet<_> = self.type_definitions.keys().collect();
+        let other_type_names: HashSet<_> = other.type_definitions.keys().collect();
+
+        self.functions.len() == other.functions.len()
+            && self.functions.iter().all(|(name, function)| {
+                other.functions.get(name).is_some_and(|other_function| {
+                    canonical_function(function, options) == canonical_function(other_function, options)
+                })
+            })
+            && self.global_constants == other.global_constants
+            && self.external_functions == other.external_functions
+            && type_names == other_type_names
+    }
+}
+
+/// Blocks in depth-first order from the entry block, then unreachable blocks
+/// in ID order
+fn block_order(function: &Function) -> Vec<BasicBlockId> {
+    let mut order = Vec::new();
+    let mut visited = HashSet::new();
+    let mut stack = vec![function.entry_block];
+
+    while let Some(block_id) = stack.pop() {
+        let Some(block) = function.basic_blocks.get(&block_id) else { continue };
+        if !visited.insert(block_id) {
+            continue;
+        }
+        order.push(block_id);
+        // Push in reverse so the first successor is visited first
+        stack.extend(cfg::successors(block).into_iter().rev());
+    }
+
+    let mut unreachable: Vec<_> = function.basic_blocks.keys().filter(|id| !visited.contains(id)).copied().collect();
+    unreachable.sort();
+    order.extend(unreachable);
+    order
+}
+
+/// Locals in order of first mention: parameters, the return local, then
+/// uses in block order, then unused locals in ID order
+fn local_order(function: &Function, blocks: &[BasicBlockId]) -> Vec<LocalId> {
+    let mut order = Vec::new();
+    let mut seen = HashSet::new();
+    let mut record = |local: LocalId| {
+        if seen.insert(local) {
+            order.push(local);
+        }
+        local
+    };
+
+    for parameter in &function.parameters {
+        record(parameter.local_id);
+    }
+    if let Some(return_local) = function.return_local {
+        record(return_local);
+    }
+    for block_id in blocks {
+        let block = &function.basic_blocks[block_id];
+        for statement in &block.statements {
+            statement.clone().map_locals(&mut record);
+        }
+        block.terminator.clone().map_locals(&mut record);
+    }
+
+    let mut unused: Vec<_> = function.locals.keys().copied().collect();
+    unused.sort();
+    for local in unused {
+        record(local);
+    }
+    order
+}
+
+/// Copy of `function` relabeled and stripped as `options` asks
+fn canonical_function(function: &Function, options: StructuralEqOptions) -> Function {
+    let blocks = block_order(function);
+    let block_map: HashMap<BasicBlockId, BasicBlockId> = if options.ignore_block_ids {
+        blocks.iter().enumerate().map(|(index, id)| (*id, index as BasicBlockId)).collect()
+    } else {
+        blocks.iter().map(|id| (*id, *id)).collect()
+    };
+    let local_map: HashMap<LocalId, LocalId> = if options.ignore_local_ids {
+        local_order(function, &blocks).into_iter().enumerate().map(|(index, id)| (id, index as LocalId)).collect()
+    } else {
+        HashMap::new()
+    };
+    let mut map_local = |local: LocalId| local_map.get(&local).copied().unwrap_or(local);
+    let mut map_block = |block: BasicBlockId| block_map.get(&block).copied().unwrap_or(block);
+
+    let mut canonical = function.clone();
+    canonical.entry_block = map_block(function.entry_block);
+    canonical.return_local = function.return_local.map(&mut map_local);
+    for parameter in &mut canonical.parameters {
+        parameter.local_id = map_local(parameter.local_id);
+    }
+
+    canonical.locals = function.locals.iter()
+        .map(|(id, local)| {
+            let mut local = local.clone();
+            if options.ignore_spans {
+                if let Some(source_info) = &mut local.source_info {
+                    source_info.span = SourceLocation::unknown();
+                }
+            }
+            (map_local(*id), local)
+        })
+        .collect();
+
+    canonical.basic_blocks = function.basic_blocks.values()
+        .map(|block| {
+            let mut block = block.clone();
+            block.id = map_block(block.id);
+            for statement in &mut block.statements {
+                statement.map_locals(&mut map_local);
+                if let (true, super::Statement::Assign { source_info, .. }) = (options.ignore_spans, statement) {
+                    source_info.span = SourceLocation::unknown();
+                }
+            }
+            block.terminator.map_locals(&mut map_local);
+            block.terminator.map_blocks(&mut map_block);
+            (block.id, block)
+        })
+        .collect();
+
+    canonical
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::{self, Identifier, PrimitiveType};
+    use crate::mir::lowering::LoweringContext;
+
+    /// `twice(x) = x + x`, or `x + 1` when `use_one` is set
+    fn twice(use_one: bool) -> ast::Function {
+        let loc = SourceLocation::unknown();
+        let integer = || Box::new(ast::TypeSpecifier::Primitive {
+            type_name: PrimitiveType::Integer,
+            source_location: SourceLocation::unknown(),
+        });
+        let x = || Box::new(ast::Expression::Variable {
+            name: Identifier::new("x".to_string(), SourceLocation::unknown()),
+            source_location: SourceLocation::unknown(),
+        });
+        let right = if use_one {
+            Box::new(ast::Expression::IntegerLiteral { value: 1, suffix: None, source_location: loc.clone() })
+        } else {
+            x()
+        };
+
+        ast::Function {
+            name: Identifier::new("twice".to_string(), loc.clone()),
+            intent: None,
+            generic_parameters: vec![],
+            parameters: vec![ast::Parameter {
+                name: Identifier::new("x".to_string(), loc.clone()),
+                param_type: integer(),
+                intent: None,
+                constraint: None,
+                passing_mode: ast::PassingMode::ByValue,
+                source_location: loc.clone(),
+            }],
+            return_type: integer(),
+            metadata: ast::FunctionMetadata {
+                preconditions: vec![],
+                postconditions: vec![],
+                invariants: vec![],
+                algorithm_hint: None,
+                performance_expectation: None,
+                complexity_expectation: None,
+                throws_exceptions: vec![],
+                thread_safe: None,
+                may_block: None,
+                pure: None,
+                optimization_hints: vec![],
+            },
+            body: ast::Block {
+                statements: vec![ast::Statement::Return {
+                    value: Some(Box::new(ast::Expression::Add { left: x(), right, source_location: loc.clone() })),
+                    source_location: loc.clone(),
+                }],
+                source_location: loc.clone(),
+            },
+            export_info: None,
+            source_location: loc,
+        }
+    }
+
+    /// Lower `function`, after `padding` other functions have used up IDs
+    fn lower_after(padding: usize, function: &ast::Function) -> Program {
+        let mut functions: Vec<_> = (0..padding)
+            .map(|index| {
+                let mut pad = twice(true);
+                pad.name = Identifier::new(format!("pad{}", index), SourceLocation::unknown());
+                pad
+            })
+            .collect();
+        functions.push(function.clone());
+
+        let ast_program = ast::Program {
+            modules: vec![ast::Module {
+                name: Identifier::new("test".to_string(), SourceLocation::unknown()),
+                intent: None,
+                imports: vec![],
+                exports: vec![],
+                type_definitions: vec![],
+                constant_declarations: vec![],
+                function_definitions: functions,
+                external_functions: vec![],
+                source_location: SourceLocation::unknown(),
+            }],
+            source_location: SourceLocation::unknown(),
+        };
+        let mut program = LoweringContext::new().lower_program(&ast_program).unwrap();
+        program.functions.retain(|name, _| !name.starts_with("pad"));
+        program
+    }
+
+    #[test]
+    fn test_renumbered_lowerings_compare_equal() {
+        let first = lower_after(0, &twice(false));
+        let second = lower_after(2, &twice(false));
+
+        let first_locals: Vec<_> = first.functions["twice"].locals.keys().copied().collect();
+        assert!(second.functions["twice"].locals.keys().all(|id| !first_locals.contains(id)));
+
+        assert!(first.structurally_equal(&second, StructuralEqOptions::default()));
+        assert!(!first.structurally_equal(&second, StructuralEqOptions {
+            ignore_local_ids: false,
+            ..StructuralEqOptions::default()
+        }));
+    }
+
+    #[test]
+    fn test_different_bodies_compare_unequal() {
+        let first = lower_after(0, &twice(false));
+        let second = lower_after(0, &twice(true));
+
+        assert!(!first.structurally_equal(&second, StructuralEqOptions::default()));
+    }
+}
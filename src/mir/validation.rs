@@ -41,6 +41,9 @@ pub enum ValidationError {
     
     /// Uninitialized local
     UninitializedLocal { local: LocalId, location: Location },
+    
+    /// Binary operation applied to operand types it does not support
+    InvalidOperands { op: BinOp, left: Type, right: Type, location: Location },
 }
 
 /// MIR validator
@@ -203,7 +206,7 @@ impl Validator {
         location: Location,
     ) {
         match term {
-            Terminator::SwitchInt { discriminant, .. } => {
+            Terminator::SwitchInt { discriminant, .. } | Terminator::CondBranch { cond: discriminant, .. } => {
                 self.collect_operand_locals(discriminant, used, location);
             }
             Terminator::Call { func, args, destination, .. } => {
@@ -281,13 +284,35 @@ impl Validator {
     }
     
     /// Check type consistency
-    fn check_types(&mut self, _function: &Function) {
-        // TODO: Implement type checking
+    fn check_types(&mut self, function: &Function) {
+        // TODO: Check the remaining operations
         // This would verify that:
-        // - Binary operations have compatible operand types
+        // - Other binary operations have compatible operand types
         // - Assignments have matching types
         // - Function calls have correct argument types
         // - etc.
+        for (block_id, block) in &function.basic_blocks {
+            for (stmt_idx, stmt) in block.statements.iter().enumerate() {
+                // Pointer arithmetic is the only source of `Offset`: a
+                // pointer advanced by an integer number of elements
+                if let Statement::Assign { rvalue: Rvalue::BinaryOp { op: BinOp::Offset, left, right }, .. } = stmt {
+                    let (Some(left), Some(right)) = (operand_type(function, left), operand_type(function, right)) else {
+                        continue;
+                    };
+                    if !left.is_pointer() || !right.is_integer() {
+                        self.errors.push(ValidationError::InvalidOperands {
+                            op: BinOp::Offset,
+                            left,
+                            right,
+                            location: Location {
+                                block: *block_id,
+                                statement_index: Some(stmt_idx),
+                            },
+                        });
+                    }
+                }
+            }
+        }
     }
     
     /// Check SSA properties
@@ -316,6 +341,29 @@ impl Validator {
     }
 }
 
+/// Type of an operand, if its place can be resolved
+fn operand_type(function: &Function, operand: &Operand) -> Option<Type> {
+    match operand {
+        Operand::Copy(place) | Operand::Move(place) => place_type(function, place),
+        Operand::Constant(constant) => Some(constant.ty.clone()),
+    }
+}
+
+/// Type of a place: its local's type narrowed by each projection
+fn place_type(function: &Function, place: &Place) -> Option<Type> {
+    let mut ty = function.locals.get(&place.local)?.ty.clone();
+    for elem in &place.projection {
+        ty = match (elem, ty) {
+            (PlaceElem::Field { ty, .. }, _) => ty.clone(),
+            (PlaceElem::Deref, Type::Pointer { target_type, .. }) => *target_type,
+            (PlaceElem::Index(_), Type::Array { element_type, .. }) => *element_type,
+            (PlaceElem::Subslice { .. }, ty @ Type::Array { .. }) => ty,
+            _ => return None,
+        };
+    }
+    Some(ty)
+}
+
 impl Default for Validator {
     fn default() -> Self {
         Self::new()
@@ -393,6 +441,10 @@ mod tests {
             parameters: vec![],
             return_type: Type::primitive(PrimitiveType::Integer),
             return_local: None,
+            is_pure: false,
+            export_symbol: None,
+            calling_convention: CallingConvention::Rust,
+            pass_control: PassControl::default(),
             locals: HashMap::new(),
             basic_blocks: HashMap::new(),
             entry_block: 0,
@@ -422,4 +474,46 @@ mod tests {
         let mut validator = Validator::new();
         assert!(validator.validate_function(&function).is_err());
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_validator_rejects_offset_of_integers() {
+        let integer = Type::primitive(PrimitiveType::Integer);
+        let mut builder = Builder::new();
+        builder.start_function(
+            "test".to_string(),
+            vec![("x".to_string(), integer.clone())],
+            integer.clone(),
+        );
+        let result = builder.new_local(integer.clone(), false);
+        builder.push_statement(Statement::Assign {
+            place: Place {
+                local: result,
+                projection: vec![],
+            },
+            rvalue: Rvalue::BinaryOp {
+                op: BinOp::Offset,
+                left: Operand::Copy(Place {
+                    local: 0,
+                    projection: vec![],
+                }),
+                right: Operand::Constant(Constant {
+                    ty: integer.clone(),
+                    value: ConstantValue::Integer(1),
+                }),
+            },
+            source_info: SourceInfo {
+                span: SourceLocation::unknown(),
+                scope: 0,
+            },
+        });
+        builder.set_terminator(Terminator::Return);
+        let function = builder.finish_function();
+        
+        let mut validator = Validator::new();
+        let errors = validator.validate_function(&function).unwrap_err();
+        assert!(errors.iter().any(|error| matches!(
+            error,
+            ValidationError::InvalidOperands { op: BinOp::Offset, .. }
+        )));
+    }
+}
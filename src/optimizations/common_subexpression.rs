@@ -18,10 +18,10 @@
 
 use super::OptimizationPass;
 use crate::mir::{
-    Function, Statement, Rvalue, Operand, LocalId, Place, BinOp, UnOp,
+    Function, Program, Statement, Rvalue, Operand, LocalId, Place, BinOp, UnOp, Constant, ConstantValue,
 };
 use crate::error::SemanticError;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Expression representation for CSE
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -36,11 +36,16 @@ enum Expression {
         operand: Operand,
     },
     Use(Operand),
+    /// Call to a function without side effects
+    PureCall {
+        func: String,
+        args: Vec<Operand>,
+    },
 }
 
 impl Expression {
     /// Create an expression from an rvalue
-    fn from_rvalue(rvalue: &Rvalue) -> Option<Self> {
+    fn from_rvalue(rvalue: &Rvalue, pure_functions: &HashSet<String>) -> Option<Self> {
         match rvalue {
             Rvalue::Use(operand) => Some(Expression::Use(operand.clone())),
             Rvalue::BinaryOp { op, left, right } => Some(Expression::BinOp {
@@ -52,6 +57,11 @@ impl Expression {
                 op: *op,
                 operand: operand.clone(),
             }),
+            Rvalue::Call { func: Operand::Constant(Constant { value: ConstantValue::String(name), .. }), args }
+                if pure_functions.contains(name) => Some(Expression::PureCall {
+                    func: name.clone(),
+                    args: args.clone(),
+                }),
             _ => None, // Only handle simple expressions for now
         }
     }
@@ -64,6 +74,7 @@ impl Expression {
                 operand_uses_local(left, local) || operand_uses_local(right, local)
             }
             Expression::UnOp { operand, .. } => operand_uses_local(operand, local),
+            Expression::PureCall { args, .. } => args.iter().any(|arg| operand_uses_local(arg, local)),
         }
     }
 }
@@ -79,12 +90,21 @@ fn operand_uses_local(operand: &Operand, local: LocalId) -> bool {
 /// Common subexpression elimination optimization pass
 pub struct CommonSubexpressionEliminationPass {
     eliminated_expressions: usize,
+    
+    /// Functions whose calls can be shared, set by `run_on_program`
+    pure_functions: HashSet<String>,
+    
+    /// Functions among `pure_functions` that read memory, whose calls are
+    /// only shared until a store or another call
+    memory_reading_functions: HashSet<String>,
 }
 
 impl CommonSubexpressionEliminationPass {
     pub fn new() -> Self {
         Self {
             eliminated_expressions: 0,
+            pure_functions: HashSet::new(),
+            memory_reading_functions: HashSet::new(),
         }
     }
     
@@ -96,12 +116,23 @@ impl CommonSubexpressionEliminationPass {
         for statement in statements.iter_mut() {
             match statement {
                 Statement::Assign { place, rvalue, .. } => {
-                    // Invalidate expressions that use the assigned local
-                    available_expressions.retain(|expr, _| !expr.uses_local(place.local));
+                    // Any other call may write through the handles it is
+                    // given, and a store through a projection may write to
+                    // memory a call read
+                    let other_call = matches!(rvalue, Rvalue::Call { .. })
+                        && Expression::from_rvalue(rvalue, &self.pure_functions).is_none();
+                    if other_call || !place.projection.is_empty() {
+                        let memory_reading = &self.memory_reading_functions;
+                        available_expressions.retain(|expr, _| {
+                            !matches!(expr, Expression::PureCall { func, .. } if memory_reading.contains(func))
+                        });
+                    }
                     
-                    // Check if this expression is already available
-                    if let Some(expr) = Expression::from_rvalue(rvalue) {
-                        if let Some(&existing_local) = available_expressions.get(&expr) {
+                    // Check if this expression is already available; the
+                    // right-hand side is evaluated before the assignment
+                    let expr = Expression::from_rvalue(rvalue, &self.pure_functions);
+                    let reused = match expr.as_ref().and_then(|expr| available_expressions.get(expr)) {
+                        Some(&existing_local) => {
                             // Replace with use of existing computation
                             *rvalue = Rvalue::Use(Operand::Copy(Place {
                                 local: existing_local,
@@ -109,8 +140,19 @@ impl CommonSubexpressionEliminationPass {
                             }));
                             changed = true;
                             self.eliminated_expressions += 1;
-                        } else {
-                            // Record this expression as available
+                            true
+                        }
+                        None => false,
+                    };
+                    
+                    // Invalidate expressions that use or were computed into the assigned local
+                    available_expressions.retain(|expr, &mut computed_by| {
+                        computed_by != place.local && !expr.uses_local(place.local)
+                    });
+                    
+                    // Record this expression as available if the local now holds its value
+                    if let Some(expr) = expr {
+                        if !reused && place.projection.is_empty() && !expr.uses_local(place.local) {
                             available_expressions.insert(expr, place.local);
                         }
                     }
@@ -146,6 +188,21 @@ impl OptimizationPass for CommonSubexpressionEliminationPass {
         
         Ok(changed)
     }
+    
+    fn run_on_program(&mut self, program: &mut Program) -> Result<bool, SemanticError> {
+        self.memory_reading_functions = program.memory_reading_function_names();
+        self.pure_functions = program.pure_function_names().into_iter()
+            .chain(self.memory_reading_functions.iter().cloned())
+            .collect();
+        
+        let mut changed = false;
+        for function in program.functions.values_mut() {
+            if !function.pass_control.disable_all {
+                changed |= self.run_on_function(function)?;
+            }
+        }
+        Ok(changed)
+    }
 }
 
 impl Default for CommonSubexpressionEliminationPass {
@@ -346,4 +403,88 @@ mod tests {
         assert!(!changed);
         assert_eq!(pass.eliminated_expressions, 0);
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_cse_of_pure_calls() {
+        let int = Type::primitive(PrimitiveType::Integer);
+        let span = SourceLocation::unknown();
+        let mut builder = Builder::new();
+        
+        builder.start_function("square".to_string(), vec![("n".to_string(), int.clone())], int.clone());
+        builder.set_terminator(crate::mir::Terminator::Return);
+        let mut square = builder.finish_function();
+        square.is_pure = true;
+        
+        builder.start_function("main".to_string(), vec![("x".to_string(), int.clone())], int.clone());
+        let x = Operand::Copy(Place { local: 0, projection: vec![] });
+        let results: Vec<LocalId> = (0..4).map(|_| builder.new_local(int.clone(), false)).collect();
+        
+        // Two calls to the pure function, then two to an impure one
+        for (i, func) in ["square", "square", "log_value", "log_value"].iter().enumerate() {
+            builder.add_call(Place { local: results[i], projection: vec![] }, func, vec![x.clone()], span.clone());
+        }
+        let main = builder.finish_function();
+        
+        let mut program = Program {
+            functions: HashMap::from([("square".to_string(), square), ("main".to_string(), main)]),
+            global_constants: HashMap::new(),
+            external_functions: HashMap::new(),
+            type_definitions: HashMap::new(),
+        };
+        
+        let mut pass = CommonSubexpressionEliminationPass::new();
+        assert!(pass.run_on_program(&mut program).unwrap());
+        assert_eq!(pass.eliminated_expressions, 1);
+        
+        let main = &program.functions["main"];
+        let statements = &main.basic_blocks[&main.entry_block].statements;
+        assert!(matches!(&statements[0], Statement::Assign { rvalue: Rvalue::Call { .. }, .. }));
+        assert!(matches!(&statements[1], Statement::Assign { rvalue: Rvalue::Use(Operand::Copy(place)), .. } if place.local == results[0]));
+        assert!(matches!(&statements[2], Statement::Assign { rvalue: Rvalue::Call { .. }, .. }));
+        assert!(matches!(&statements[3], Statement::Assign { rvalue: Rvalue::Call { .. }, .. }));
+    }
+    
+    #[test]
+    fn test_memory_reads_and_allocations_are_not_shared_across_writes() {
+        let int = Type::primitive(PrimitiveType::Integer);
+        let string = Type::primitive(PrimitiveType::String);
+        let span = SourceLocation::unknown();
+        let mut builder = Builder::new();
+        
+        builder.start_function(
+            "main".to_string(),
+            vec![("values".to_string(), Type::array(int.clone(), None)), ("name".to_string(), string.clone())],
+            int.clone(),
+        );
+        let values = Operand::Copy(Place { local: 0, projection: vec![] });
+        let name = Operand::Copy(Place { local: 1, projection: vec![] });
+        let zero = Operand::Constant(Constant { ty: int.clone(), value: ConstantValue::Integer(0) });
+        let results: Vec<LocalId> = (0..5).map(|_| builder.new_local(int.clone(), false)).collect();
+        let calls = [
+            ("array_get", vec![values.clone(), zero.clone()]),
+            ("array_set", vec![values.clone(), zero.clone(), zero.clone()]),
+            ("array_get", vec![values, zero]),
+            ("string_concat", vec![name.clone(), name.clone()]),
+            ("string_concat", vec![name.clone(), name]),
+        ];
+        for (result, (func, args)) in results.iter().zip(calls) {
+            builder.add_call(Place { local: *result, projection: vec![] }, func, args, span.clone());
+        }
+        let main = builder.finish_function();
+        
+        let mut program = Program {
+            functions: HashMap::from([("main".to_string(), main)]),
+            global_constants: HashMap::new(),
+            external_functions: HashMap::new(),
+            type_definitions: HashMap::new(),
+        };
+        
+        // array_set may change what array_get reads, and every concatenation
+        // is a fresh string
+        let mut pass = CommonSubexpressionEliminationPass::new();
+        assert!(!pass.run_on_program(&mut program).unwrap());
+        let main = &program.functions["main"];
+        assert!(main.basic_blocks[&main.entry_block].statements.iter()
+            .all(|statement| matches!(statement, Statement::Assign { rvalue: Rvalue::Call { .. }, .. })));
+    }
+}
@@ -22,16 +22,52 @@ use crate::mir::{
 };
 use crate::types::Type;
 use crate::ast::PrimitiveType;
-use crate::error::SemanticError;
+use crate::error::{SemanticError, SourceLocation};
+
+/// What constant folding does when integer arithmetic overflows its type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowMode {
+    /// Wrap around to the type's width, as the generated code would
+    Wrap,
+    /// Report the overflowing expression as a compile error
+    Error,
+}
 
 /// Constant folding optimization pass
 pub struct ConstantFoldingPass {
     changed: bool,
+    overflow_mode: OverflowMode,
 }
 
 impl ConstantFoldingPass {
     pub fn new() -> Self {
-        Self { changed: false }
+        Self::with_overflow_mode(OverflowMode::Wrap)
+    }
+    
+    pub fn with_overflow_mode(overflow_mode: OverflowMode) -> Self {
+        Self { changed: false, overflow_mode }
+    }
+    
+    /// Fit a folded integer into `ty`, wrapping or failing on overflow
+    fn fit_integer(
+        &self,
+        value: i128,
+        ty: &Type,
+        describe: impl FnOnce() -> String,
+        location: &SourceLocation,
+    ) -> Result<i128, SemanticError> {
+        let Some(bits) = ty.signed_bits() else { return Ok(value) };
+        let shift = 128 - bits;
+        let wrapped = (value << shift) >> shift;
+        if wrapped == value || self.overflow_mode == OverflowMode::Wrap {
+            return Ok(wrapped);
+        }
+        
+        Err(SemanticError::InvalidOperation {
+            operation: "constant arithmetic".to_string(),
+            reason: format!("{} overflows a {}-bit integer", describe(), bits),
+            location: location.clone(),
+        })
     }
     
     /// Fold a binary operation on constants
@@ -129,12 +165,16 @@ impl ConstantFoldingPass {
     }
     
     /// Optimize an rvalue
-    fn optimize_rvalue(&mut self, rvalue: &mut Rvalue) {
+    fn optimize_rvalue(&mut self, rvalue: &mut Rvalue, location: &SourceLocation) -> Result<(), SemanticError> {
         match rvalue {
             Rvalue::BinaryOp { op, left, right } => {
                 if let (Operand::Constant(left_const), Operand::Constant(right_const)) = (left, right) {
-                    if let Some(result) = self.fold_binary_op(*op, &left_const.value, &right_const.value) {
+                    if let Some(mut result) = self.fold_binary_op(*op, &left_const.value, &right_const.value) {
                         let result_type = self.get_binary_result_type(*op, &left_const.ty);
+                        if let ConstantValue::Integer(value) = result {
+                            let describe = || format!("{:?} of {:?} and {:?}", op, left_const.value, right_const.value);
+                            result = ConstantValue::Integer(self.fit_integer(value, &result_type, describe, location)?);
+                        }
                         *rvalue = Rvalue::Use(Operand::Constant(Constant {
                             ty: result_type,
                             value: result,
@@ -146,7 +186,11 @@ impl ConstantFoldingPass {
             
             Rvalue::UnaryOp { op, operand } => {
                 if let Operand::Constant(const_operand) = operand {
-                    if let Some(result) = self.fold_unary_op(*op, &const_operand.value) {
+                    if let Some(mut result) = self.fold_unary_op(*op, &const_operand.value) {
+                        if let ConstantValue::Integer(value) = result {
+                            let describe = || format!("{:?} of {:?}", op, const_operand.value);
+                            result = ConstantValue::Integer(self.fit_integer(value, &const_operand.ty, describe, location)?);
+                        }
                         *rvalue = Rvalue::Use(Operand::Constant(Constant {
                             ty: const_operand.ty.clone(),
                             value: result,
@@ -158,6 +202,8 @@ impl ConstantFoldingPass {
             
             _ => {}
         }
+        
+        Ok(())
     }
 }
 
@@ -171,8 +217,8 @@ impl OptimizationPass for ConstantFoldingPass {
         
         for block in function.basic_blocks.values_mut() {
             for statement in &mut block.statements {
-                if let Statement::Assign { rvalue, .. } = statement {
-                    self.optimize_rvalue(rvalue);
+                if let Statement::Assign { rvalue, source_info, .. } = statement {
+                    self.optimize_rvalue(rvalue, &source_info.span)?;
                 }
             }
         }
@@ -350,4 +396,50 @@ mod tests {
             panic!("Expected assignment statement");
         }
     }
+    
+    /// Fold `2_000_000_000 + 2_000_000_000` as a 32-bit `Integer`
+    fn fold_overflowing_add(mode: OverflowMode) -> Result<Function, SemanticError> {
+        let mut builder = Builder::new();
+        builder.start_function("test".to_string(), vec![], Type::primitive(PrimitiveType::Integer));
+        let temp = builder.new_local(Type::primitive(PrimitiveType::Integer), false);
+        let operand = || Operand::Constant(Constant {
+            ty: Type::primitive(PrimitiveType::Integer),
+            value: ConstantValue::Integer(2_000_000_000),
+        });
+        builder.push_statement(Statement::Assign {
+            place: Place { local: temp, projection: vec![] },
+            rvalue: Rvalue::BinaryOp { op: BinOp::Add, left: operand(), right: operand() },
+            source_info: SourceInfo {
+                span: SourceLocation::new("overflow.aether".to_string(), 3, 7, 0),
+                scope: 0,
+            },
+        });
+        
+        let mut function = builder.finish_function();
+        ConstantFoldingPass::with_overflow_mode(mode).run_on_function(&mut function)?;
+        Ok(function)
+    }
+    
+    #[test]
+    fn test_overflow_is_error_in_strict_mode() {
+        match fold_overflowing_add(OverflowMode::Error) {
+            Err(SemanticError::InvalidOperation { reason, location, .. }) => {
+                assert!(reason.contains("32-bit"));
+                assert_eq!((location.line, location.column), (3, 7));
+            }
+            other => panic!("expected overflow error, got {:?}", other.map(|_| ())),
+        }
+    }
+    
+    #[test]
+    fn test_overflow_wraps_in_permissive_mode() {
+        let function = fold_overflowing_add(OverflowMode::Wrap).unwrap();
+        let block = function.basic_blocks.values().next().unwrap();
+        match &block.statements[0] {
+            Statement::Assign { rvalue: Rvalue::Use(Operand::Constant(constant)), .. } => {
+                assert_eq!(constant.value, ConstantValue::Integer(4_000_000_000i128 - (1i128 << 32)));
+            }
+            other => panic!("expected folded constant, got {:?}", other),
+        }
+    }
 }
\ No newline at end of file
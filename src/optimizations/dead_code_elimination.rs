@@ -62,6 +62,13 @@ impl DeadCodeEliminationPass {
                                 worklist.push(targets.otherwise);
                             }
                         }
+                        Terminator::CondBranch { true_block, false_block, .. } => {
+                            for target in [true_block, false_block] {
+                                if !reachable.contains(target) {
+                                    worklist.push(*target);
+                                }
+                            }
+                        }
                         Terminator::Call { target, cleanup, .. } => {
                             if let Some(target) = target {
                                 if !reachable.contains(target) {
@@ -151,6 +158,7 @@ impl DeadCodeEliminationPass {
             Terminator::SwitchInt { discriminant, .. } => {
                 self.local_used_in_operand(discriminant, local)
             }
+            Terminator::CondBranch { cond, .. } => self.local_used_in_operand(cond, local),
             Terminator::Call { func, args, destination, .. } => {
                 self.local_used_in_operand(func, local) ||
                 args.iter().any(|arg| self.local_used_in_operand(arg, local)) ||
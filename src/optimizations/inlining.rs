@@ -18,8 +18,8 @@
 
 use super::OptimizationPass;
 use std::collections::HashSet;
-use crate::mir::{Function, Program, Statement, Terminator, Rvalue, Operand, Place, LocalId,
-                 BasicBlockId, SourceInfo};
+use crate::mir::{Function, Program, Statement, Terminator, Rvalue, Operand, Place, BasicBlock, BasicBlockId,
+                 Constant, ConstantValue};
 use crate::error::SemanticError;
 use std::collections::HashMap;
 
@@ -31,6 +31,9 @@ pub struct InliningPass {
     
     /// Functions already inlined to prevent recursion
     inlined_functions: HashSet<String>,
+    
+    /// Largest callee, in basic blocks, that `inline_single_use` will inline
+    max_inline_blocks: usize,
 }
 
 impl InliningPass {
@@ -38,6 +41,7 @@ impl InliningPass {
         Self {
             threshold: 20,
             inlined_functions: HashSet::new(),
+            max_inline_blocks: 8,
         }
     }
     
@@ -60,7 +64,7 @@ impl InliningPass {
             // Add cost for complex terminators
             match &block.terminator {
                 Terminator::Call { .. } => cost += 5, // Calls are expensive
-                Terminator::SwitchInt { .. } => cost += 2, // Branches have some cost
+                Terminator::SwitchInt { .. } | Terminator::CondBranch { .. } => cost += 2, // Branches have some cost
                 _ => cost += 1,
             }
         }
@@ -118,7 +122,7 @@ impl OptimizationPass for InliningPass {
     }
     
     fn run_on_program(&mut self, program: &mut Program) -> Result<bool, SemanticError> {
-        let changed = false;
+        let changed = inline_single_use(program, self.max_inline_blocks);
         
         // Find functions that are candidates for inlining
         let mut inline_candidates = Vec::new();
@@ -160,6 +164,188 @@ impl OptimizationPass for InliningPass {
     }
 }
 
+/// Inline every function that is called from exactly one `Rvalue::Call` site
+///
+/// The callee's locals and blocks are copied into the caller under fresh ids,
+/// its parameters are assigned from the call arguments and each `Return`
+/// stores the callee's return local into the call's destination before
+/// jumping to the rest of the caller's block. Callees with more than
+/// `max_blocks` blocks, self-calls, callees marked not to be inlined and
+/// callers that opted out of optimization are left alone. Callees marked to
+/// be inlined are always inlined, at every call site and whatever their
+/// size, unless they can call themselves directly or through other
+/// functions. The callee itself is kept, since it may still be exported.
+/// Returns true if anything was inlined.
+pub fn inline_single_use(program: &mut Program, max_blocks: usize) -> bool {
+    let mut changed = false;
+    
+    // Inline one site at a time, since inlining moves the remaining call sites
+    while let Some(site) = find_single_use_site(program, max_blocks) {
+        let callee = program.functions[&site.callee].clone();
+        let caller = program.functions.get_mut(&site.caller).expect("caller exists");
+        inline_call_site(caller, &callee, site.block, site.statement);
+        changed = true;
+    }
+    
+    changed
+}
+
+/// A call statement that is the only use of its callee
+struct CallSite {
+    caller: String,
+    callee: String,
+    block: BasicBlockId,
+    statement: usize,
+}
+
+/// Name of the program function called by a constant call operand
+fn called_function<'a>(program: &Program, func: &'a Operand) -> Option<&'a str> {
+    match func {
+        Operand::Constant(Constant { value: ConstantValue::String(name), .. })
+            if program.functions.contains_key(name) => Some(name),
+        _ => None,
+    }
+}
+
+/// Find the next call site whose callee is called nowhere else, or must be
+/// inlined everywhere
+fn find_single_use_site(program: &Program, max_blocks: usize) -> Option<CallSite> {
+    let mut use_counts: HashMap<&str, usize> = HashMap::new();
+    let mut sites = Vec::new();
+    
+    for (caller_name, function) in &program.functions {
+        for (block_id, block) in &function.basic_blocks {
+            for (index, statement) in block.statements.iter().enumerate() {
+                if let Statement::Assign { rvalue: Rvalue::Call { func, .. }, .. } = statement {
+                    if let Some(callee) = called_function(program, func) {
+                        *use_counts.entry(callee).or_insert(0) += 1;
+                        sites.push(CallSite {
+                            caller: caller_name.clone(),
+                            callee: callee.to_string(),
+                            block: *block_id,
+                            statement: index,
+                        });
+                    }
+                }
+            }
+            
+            // Terminator calls are not inlined but still count as uses
+            if let Terminator::Call { func, .. } = &block.terminator {
+                if let Some(callee) = called_function(program, func) {
+                    *use_counts.entry(callee).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+    
+    sites.into_iter()
+        .filter(|site| site.caller != site.callee)
+        .filter(|site| {
+            let callee = &program.functions[&site.callee];
+            if callee.pass_control.requires_inlining() {
+                // Inlining a callee that can reach itself again, directly or
+                // through other functions, would never run out of call sites
+                !reaches_itself(program, &site.callee)
+            } else {
+                use_counts[site.callee.as_str()] == 1
+                    && callee.basic_blocks.len() <= max_blocks
+                    && callee.pass_control.allows_inlining()
+            }
+        })
+        .filter(|site| !program.functions[&site.caller].pass_control.disable_all)
+        .min_by(|a, b| (&a.caller, &a.callee).cmp(&(&b.caller, &b.callee)))
+}
+
+/// Whether the program function `name` can call itself, directly or
+/// through a chain of other program functions
+fn reaches_itself(program: &Program, name: &str) -> bool {
+    let mut visited = HashSet::new();
+    let mut pending = vec![name];
+    while let Some(current) = pending.pop() {
+        for block in program.functions[current].basic_blocks.values() {
+            let statement_calls = block.statements.iter().filter_map(|statement| match statement {
+                Statement::Assign { rvalue: Rvalue::Call { func, .. }, .. } => called_function(program, func),
+                _ => None,
+            });
+            let terminator_call = match &block.terminator {
+                Terminator::Call { func, .. } => called_function(program, func),
+                _ => None,
+            };
+            for callee in statement_calls.chain(terminator_call) {
+                if callee == name {
+                    return true;
+                }
+                if visited.insert(callee) {
+                    pending.push(callee);
+                }
+            }
+        }
+    }
+    false
+}
+
+/// Splice `callee` into `caller` in place of the call at `block`/`statement`
+fn inline_call_site(caller: &mut Function, callee: &Function, block: BasicBlockId, statement: usize) {
+    let local_offset = caller.locals.keys().max().map_or(0, |id| id + 1);
+    let block_offset = caller.basic_blocks.keys().max().map_or(0, |id| id + 1);
+    let continuation = block_offset + callee.basic_blocks.keys().max().map_or(0, |id| id + 1);
+    
+    // Split the calling block around the call
+    let call_block = caller.basic_blocks.get_mut(&block).expect("call block exists");
+    let tail = call_block.statements.split_off(statement + 1);
+    let (destination, args, source_info) = match call_block.statements.pop() {
+        Some(Statement::Assign { place, rvalue: Rvalue::Call { args, .. }, source_info }) => (place, args, source_info),
+        _ => unreachable!("call site must be a call assignment"),
+    };
+    
+    for (parameter, arg) in callee.parameters.iter().zip(args) {
+        call_block.statements.push(Statement::Assign {
+            place: Place { local: parameter.local_id + local_offset, projection: vec![] },
+            rvalue: Rvalue::Use(arg),
+            source_info: source_info.clone(),
+        });
+    }
+    let original_terminator = std::mem::replace(
+        &mut call_block.terminator,
+        Terminator::Goto { target: callee.entry_block + block_offset },
+    );
+    caller.basic_blocks.insert(continuation, BasicBlock {
+        id: continuation,
+        statements: tail,
+        terminator: original_terminator,
+    });
+    
+    for (local_id, local) in &callee.locals {
+        caller.locals.insert(local_id + local_offset, local.clone());
+    }
+    
+    for callee_block in callee.basic_blocks.values() {
+        let mut inlined = callee_block.clone();
+        inlined.id += block_offset;
+        for statement in &mut inlined.statements {
+            statement.map_locals(&mut |local| local + local_offset);
+        }
+        inlined.terminator.map_locals(&mut |local| local + local_offset);
+        inlined.terminator.map_blocks(&mut |target| target + block_offset);
+        
+        if let Terminator::Return = inlined.terminator {
+            if let Some(return_local) = callee.return_local {
+                inlined.statements.push(Statement::Assign {
+                    place: destination.clone(),
+                    rvalue: Rvalue::Use(Operand::Copy(Place {
+                        local: return_local + local_offset,
+                        projection: vec![],
+                    })),
+                    source_info: source_info.clone(),
+                });
+            }
+            inlined.terminator = Terminator::Goto { target: continuation };
+        }
+        
+        caller.basic_blocks.insert(inlined.id, inlined);
+    }
+}
+
 impl Default for InliningPass {
     fn default() -> Self {
         Self::new()
@@ -169,9 +355,9 @@ impl Default for InliningPass {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::mir::{Builder, Place, SourceInfo, Constant, ConstantValue};
+    use crate::mir::{Builder, Place, SourceInfo, Constant, ConstantValue, PassControl};
     use crate::types::Type;
-    use crate::ast::PrimitiveType;
+    use crate::ast::{OptimizationHint, PrimitiveType};
     use crate::error::SourceLocation;
     
     #[test]
@@ -281,4 +467,228 @@ mod tests {
         // Function should still exist (not actually inlined in this simplified implementation)
         assert!(program.functions.contains_key("small"));
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_inline_single_use_helper() {
+        let int = Type::primitive(PrimitiveType::Integer);
+        let span = SourceLocation::unknown();
+        let mut program = Program {
+            functions: HashMap::new(),
+            global_constants: HashMap::new(),
+            external_functions: HashMap::new(),
+            type_definitions: HashMap::new(),
+        };
+        
+        // add_one(x) = x + 1, in a single block
+        let mut builder = Builder::new();
+        builder.start_function("add_one".to_string(), vec![("x".to_string(), int.clone())], int.clone());
+        let result = builder.new_local(int.clone(), false);
+        builder.add_assign(
+            Place { local: result, projection: vec![] },
+            Rvalue::BinaryOp {
+                op: crate::mir::BinOp::Add,
+                left: Operand::Copy(Place { local: 0, projection: vec![] }),
+                right: Operand::Constant(Constant { ty: int.clone(), value: ConstantValue::Integer(1) }),
+            },
+            span.clone(),
+        );
+        builder.terminate(Terminator::Return);
+        let mut add_one = builder.finish_function();
+        add_one.return_local = Some(result);
+        program.functions.insert("add_one".to_string(), add_one);
+        
+        // main() = add_one(41)
+        let mut builder = Builder::new();
+        builder.start_function("main".to_string(), vec![], int.clone());
+        let value = builder.new_local(int.clone(), false);
+        let args = vec![Operand::Constant(Constant { ty: int.clone(), value: ConstantValue::Integer(41) })];
+        builder.add_call(Place { local: value, projection: vec![] }, "add_one", args, span);
+        builder.terminate(Terminator::Return);
+        let mut main = builder.finish_function();
+        main.return_local = Some(value);
+        program.functions.insert("main".to_string(), main);
+        
+        assert!(inline_single_use(&mut program, 1));
+        
+        let main = &program.functions["main"];
+        let calls = main.basic_blocks.values()
+            .flat_map(|block| &block.statements)
+            .filter(|statement| matches!(statement, Statement::Assign { rvalue: Rvalue::Call { .. }, .. }))
+            .count();
+        assert_eq!(calls, 0);
+        assert_eq!(main.basic_blocks.len(), 3);
+        assert!(crate::mir::validation::Validator::new().validate_function(main).is_ok());
+        
+        // The argument feeds the callee's parameter, and the result lands in `value`
+        let entry = &main.basic_blocks[&main.entry_block];
+        assert!(matches!(&entry.statements[0], Statement::Assign {
+            rvalue: Rvalue::Use(Operand::Constant(Constant { value: ConstantValue::Integer(41), .. })), ..
+        }));
+        assert!(main.basic_blocks.values().any(|block| block.statements.iter().any(|statement| matches!(
+            statement,
+            Statement::Assign { place, rvalue: Rvalue::Use(Operand::Copy(_)), .. } if place.local == value
+        ))));
+        
+        // Nothing calls add_one any more, so a second run is a no-op
+        assert!(!inline_single_use(&mut program, 1));
+    }
+    
+    #[test]
+    fn test_inline_single_use_respects_limits() {
+        let int = Type::primitive(PrimitiveType::Integer);
+        let span = SourceLocation::unknown();
+        let mut program = Program {
+            functions: HashMap::new(),
+            global_constants: HashMap::new(),
+            external_functions: HashMap::new(),
+            type_definitions: HashMap::new(),
+        };
+        
+        // A function whose only call site is itself is never inlined
+        let mut builder = Builder::new();
+        builder.start_function("spin".to_string(), vec![], int.clone());
+        let value = builder.new_local(int.clone(), false);
+        builder.add_call(Place { local: value, projection: vec![] }, "spin", vec![], span.clone());
+        builder.terminate(Terminator::Return);
+        program.functions.insert("spin".to_string(), builder.finish_function());
+        
+        // A two-block callee exceeds a one-block limit
+        let mut builder = Builder::new();
+        builder.start_function("two_blocks".to_string(), vec![], int.clone());
+        let next = builder.new_block();
+        builder.terminate(Terminator::Goto { target: next });
+        builder.switch_to_block(next);
+        builder.terminate(Terminator::Return);
+        program.functions.insert("two_blocks".to_string(), builder.finish_function());
+        
+        let mut builder = Builder::new();
+        builder.start_function("main".to_string(), vec![], int.clone());
+        let value = builder.new_local(int, false);
+        builder.add_call(Place { local: value, projection: vec![] }, "two_blocks", vec![], span);
+        builder.terminate(Terminator::Return);
+        program.functions.insert("main".to_string(), builder.finish_function());
+        
+        assert!(!inline_single_use(&mut program, 1));
+        assert!(inline_single_use(&mut program, 2));
+    }
+    
+    #[test]
+    fn test_no_inline_callee_is_not_inlined() {
+        let int = Type::primitive(PrimitiveType::Integer);
+        let span = SourceLocation::unknown();
+        let mut program = Program {
+            functions: HashMap::new(),
+            global_constants: HashMap::new(),
+            external_functions: HashMap::new(),
+            type_definitions: HashMap::new(),
+        };
+        
+        let mut builder = Builder::new();
+        builder.start_function("helper".to_string(), vec![], int.clone());
+        builder.terminate(Terminator::Return);
+        let mut helper = builder.finish_function();
+        helper.pass_control.no_inline = true;
+        program.functions.insert("helper".to_string(), helper);
+        
+        let mut builder = Builder::new();
+        builder.start_function("main".to_string(), vec![], int.clone());
+        let value = builder.new_local(int, false);
+        builder.add_call(Place { local: value, projection: vec![] }, "helper", vec![], span);
+        builder.terminate(Terminator::Return);
+        program.functions.insert("main".to_string(), builder.finish_function());
+        
+        // A single-use, single-block callee would otherwise be inlined
+        assert!(!InliningPass::new().run_on_program(&mut program).unwrap());
+        program.functions.get_mut("helper").unwrap().pass_control.no_inline = false;
+        assert!(InliningPass::new().run_on_program(&mut program).unwrap());
+    }
+    
+    /// Whether `function` contains a call to the program function `name`
+    fn calls_function(program: &Program, function: &Function, name: &str) -> bool {
+        function.basic_blocks.values().any(|block| {
+            let statement_calls = block.statements.iter().any(|statement| matches!(
+                statement,
+                Statement::Assign { rvalue: Rvalue::Call { func, .. }, .. } if called_function(program, func) == Some(name)
+            ));
+            let terminator_calls = matches!(
+                &block.terminator,
+                Terminator::Call { func, .. } if called_function(program, func) == Some(name)
+            );
+            statement_calls || terminator_calls
+        })
+    }
+    
+    #[test]
+    fn test_inline_always_callee_is_inlined_above_size_limit() {
+        let int = Type::primitive(PrimitiveType::Integer);
+        let span = SourceLocation::unknown();
+        let mut program = Program {
+            functions: HashMap::new(),
+            global_constants: HashMap::new(),
+            external_functions: HashMap::new(),
+            type_definitions: HashMap::new(),
+        };
+        
+        // A helper with more blocks than the default limit, called twice
+        let mut builder = Builder::new();
+        builder.start_function("helper".to_string(), vec![], int.clone());
+        for _ in 0..InliningPass::new().max_inline_blocks {
+            let next = builder.new_block();
+            builder.terminate(Terminator::Goto { target: next });
+            builder.switch_to_block(next);
+        }
+        builder.terminate(Terminator::Return);
+        let mut helper = builder.finish_function();
+        helper.pass_control = PassControl::from_hints(&[OptimizationHint::InlineAlways]);
+        program.functions.insert("helper".to_string(), helper);
+        
+        let mut builder = Builder::new();
+        builder.start_function("main".to_string(), vec![], int.clone());
+        for _ in 0..2 {
+            let value = builder.new_local(int.clone(), false);
+            builder.add_call(Place { local: value, projection: vec![] }, "helper", vec![], span.clone());
+        }
+        builder.terminate(Terminator::Return);
+        program.functions.insert("main".to_string(), builder.finish_function());
+        
+        let calls_helper = |program: &Program| calls_function(program, &program.functions["main"], "helper");
+        let mut without_hint = program.clone();
+        without_hint.functions.get_mut("helper").unwrap().pass_control = PassControl::default();
+        assert!(!InliningPass::new().run_on_program(&mut without_hint).unwrap());
+        assert!(calls_helper(&without_hint));
+        
+        assert!(InliningPass::new().run_on_program(&mut program).unwrap());
+        assert!(!calls_helper(&program));
+    }
+    
+    #[test]
+    fn test_mutually_recursive_inline_always_functions_are_kept() {
+        let int = Type::primitive(PrimitiveType::Integer);
+        let span = SourceLocation::unknown();
+        let mut program = Program {
+            functions: HashMap::new(),
+            global_constants: HashMap::new(),
+            external_functions: HashMap::new(),
+            type_definitions: HashMap::new(),
+        };
+        
+        // is_even calls is_odd and is_odd calls is_even, both marked to be
+        // inlined always, and main calls is_even
+        for (name, callee) in [("is_even", "is_odd"), ("is_odd", "is_even"), ("main", "is_even")] {
+            let mut builder = Builder::new();
+            builder.start_function(name.to_string(), vec![], int.clone());
+            let value = builder.new_local(int.clone(), false);
+            builder.add_call(Place { local: value, projection: vec![] }, callee, vec![], span.clone());
+            builder.terminate(Terminator::Return);
+            let mut function = builder.finish_function();
+            if name != "main" {
+                function.pass_control = PassControl::from_hints(&[OptimizationHint::InlineAlways]);
+            }
+            program.functions.insert(name.to_string(), function);
+        }
+        
+        // Inlining either one would bring back a call to it, forever
+        assert!(!InliningPass::new().run_on_program(&mut program).unwrap());
+        assert!(calls_function(&program, &program.functions["main"], "is_even"));
+    }
+}
@@ -819,6 +819,9 @@ impl InterproceduralAnalysisPass {
         let mut changed = false;
         
         for function in program.functions.values_mut() {
+            if function.pass_control.disable_all {
+                continue;
+            }
             if self.propagate_constants_in_function(function)? {
                 changed = true;
             }
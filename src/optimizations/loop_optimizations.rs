@@ -419,6 +419,7 @@ impl LoopOptimizationPass {
                 all_targets.push(targets.otherwise);
                 all_targets
             },
+            Terminator::CondBranch { true_block, false_block, .. } => vec![*true_block, *false_block],
             Terminator::Return => vec![],
             Terminator::Call { target, cleanup, .. } => {
                 let mut targets = vec![];
@@ -15,12 +15,16 @@
 //! Optimization passes for MIR
 //! 
 //! Implements fundamental optimization techniques including dead code elimination,
-//! constant folding, and common subexpression elimination.
+//! constant folding, common subexpression elimination, loop-invariant code
+//! motion and tail-recursion elimination.
 
 pub mod constant_folding;
 pub mod dead_code_elimination;
 pub mod common_subexpression;
 pub mod inlining;
+pub mod renumber_locals;
+pub mod licm;
+pub mod tail_recursion;
 
 // Advanced optimization passes
 pub mod whole_program;
@@ -40,11 +44,14 @@ pub trait OptimizationPass {
     /// Run the optimization pass on a function
     fn run_on_function(&mut self, function: &mut Function) -> Result<bool, SemanticError>;
     
-    /// Run the optimization pass on a program
+    /// Run the optimization pass on a program, skipping functions that opted
+    /// out of optimization
     fn run_on_program(&mut self, program: &mut Program) -> Result<bool, SemanticError> {
         let mut changed = false;
         for function in program.functions.values_mut() {
-            changed |= self.run_on_function(function)?;
+            if !function.pass_control.disable_all {
+                changed |= self.run_on_function(function)?;
+            }
         }
         Ok(changed)
     }
@@ -91,11 +98,22 @@ impl OptimizationManager {
             }
         }
         
+        // Close the gaps left by removed locals
+        for function in program.functions.values_mut() {
+            if !function.pass_control.disable_all {
+                renumber_locals::renumber_locals(function);
+            }
+        }
+        
         Ok(())
     }
     
     /// Run all optimization passes on a function
     pub fn optimize_function(&mut self, function: &mut Function) -> Result<(), SemanticError> {
+        if function.pass_control.disable_all {
+            return Ok(());
+        }
+        
         for _iteration in 0..self.max_iterations {
             let mut any_changed = false;
             
@@ -110,6 +128,8 @@ impl OptimizationManager {
             }
         }
         
+        renumber_locals::renumber_locals(function);
+        
         Ok(())
     }
     
@@ -120,6 +140,8 @@ impl OptimizationManager {
         // Add optimization passes in order
         manager.add_pass(Box::new(constant_folding::ConstantFoldingPass::new()));
         manager.add_pass(Box::new(dead_code_elimination::DeadCodeEliminationPass::new()));
+        manager.add_pass(Box::new(tail_recursion::TailRecursionEliminationPass::new()));
+        manager.add_pass(Box::new(licm::LoopInvariantCodeMotionPass::new()));
         manager.add_pass(Box::new(common_subexpression::CommonSubexpressionEliminationPass::new()));
         
         manager
@@ -134,6 +156,7 @@ impl OptimizationManager {
         manager.add_pass(Box::new(dead_code_elimination::DeadCodeEliminationPass::new()));
         
         // Advanced loop optimizations
+        manager.add_pass(Box::new(licm::LoopInvariantCodeMotionPass::new()));
         manager.add_pass(Box::new(loop_optimizations::LoopOptimizationPass::new()));
         
         // Interprocedural analysis
@@ -0,0 +1,104 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Local renumbering
+//!
+//! Passes that remove locals leave gaps in `LocalId` numbering. This compacts
+//! the remaining locals into `0..n`, keeping their relative order.
+
+use crate::mir::{Function, LocalId};
+use std::collections::HashMap;
+
+/// Renumber the locals of `function` contiguously from zero, rewriting every
+/// reference. Returns whether any local changed its ID.
+pub fn renumber_locals(function: &mut Function) -> bool {
+    let mut old_ids: Vec<LocalId> = function.locals.keys().copied().collect();
+    old_ids.sort();
+    let local_map: HashMap<LocalId, LocalId> = old_ids.iter()
+        .enumerate()
+        .map(|(index, id)| (*id, index as LocalId))
+        .collect();
+    if local_map.iter().all(|(old, new)| old == new) {
+        return false;
+    }
+    let mut map_local = |local: LocalId| local_map.get(&local).copied().unwrap_or(local);
+
+    function.locals = std::mem::take(&mut function.locals)
+        .into_iter()
+        .map(|(id, local)| (map_local(id), local))
+        .collect();
+    function.return_local = function.return_local.map(&mut map_local);
+    for parameter in &mut function.parameters {
+        parameter.local_id = map_local(parameter.local_id);
+    }
+    for block in function.basic_blocks.values_mut() {
+        for statement in &mut block.statements {
+            statement.map_locals(&mut map_local);
+        }
+        block.terminator.map_locals(&mut map_local);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mir::{Builder, Statement, Rvalue, Operand, Place, SourceInfo, Terminator};
+    use crate::types::Type;
+    use crate::ast::PrimitiveType;
+    use crate::error::SourceLocation;
+
+    #[test]
+    fn test_removed_local_leaves_no_gap() {
+        let integer = Type::primitive(PrimitiveType::Integer);
+        let mut builder = Builder::new();
+        builder.start_function("test".to_string(), vec![("x".to_string(), integer.clone())], integer.clone());
+        let dead = builder.new_local(integer.clone(), false);
+        let result = builder.new_local(integer.clone(), false);
+        builder.push_statement(Statement::Assign {
+            place: Place {
+                local: result,
+                projection: vec![],
+            },
+            rvalue: Rvalue::Use(Operand::Copy(Place {
+                local: 0,
+                projection: vec![],
+            })),
+            source_info: SourceInfo {
+                span: SourceLocation::unknown(),
+                scope: 0,
+            },
+        });
+        builder.set_terminator(Terminator::Return);
+        let mut function = builder.finish_function();
+        function.return_local = Some(result);
+
+        function.locals.remove(&dead);
+        assert!(renumber_locals(&mut function));
+
+        let mut ids: Vec<_> = function.locals.keys().copied().collect();
+        ids.sort();
+        assert_eq!(ids, vec![0, 1]);
+        assert_eq!(function.parameters[0].local_id, 0);
+        assert_eq!(function.return_local, Some(1));
+        let block = &function.basic_blocks[&function.entry_block];
+        assert!(matches!(
+            &block.statements[0],
+            Statement::Assign { place: Place { local: 1, .. }, rvalue: Rvalue::Use(Operand::Copy(Place { local: 0, .. })), .. }
+        ));
+
+        // Already contiguous: nothing to do
+        assert!(!renumber_locals(&mut function));
+    }
+}
@@ -0,0 +1,297 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tail-recursion elimination
+//!
+//! Rewrites `return f(args)` inside `f` into a loop: the arguments are
+//! assigned to the parameters and control jumps back to the start of the
+//! body, so self-recursion runs in constant stack. A call is only rewritten
+//! when nothing but storage markers and jumps lie between it and the
+//! function's `Return`, so postconditions, `finally` blocks and deferred
+//! expressions keep their calls as real calls.
+
+use super::OptimizationPass;
+use crate::mir::{
+    BasicBlock, BasicBlockId, Constant, ConstantValue, Function, Local, Operand, Place, Rvalue, SourceInfo,
+    Statement, Terminator,
+};
+use crate::types::{OwnershipKind, Type};
+use crate::error::SemanticError;
+use std::collections::HashSet;
+
+/// Tail-recursion elimination pass
+pub struct TailRecursionEliminationPass {
+    eliminated_calls: usize,
+}
+
+impl TailRecursionEliminationPass {
+    pub fn new() -> Self {
+        Self { eliminated_calls: 0 }
+    }
+    
+    /// Number of tail calls turned into jumps so far
+    pub fn eliminated_calls(&self) -> usize {
+        self.eliminated_calls
+    }
+    
+    /// Whether a function can run its body again in the same frame. Owned
+    /// locals are freed when a call returns, so looping would leak them, and
+    /// a reference could point into a local the next iteration overwrites.
+    fn can_reuse_frame(function: &Function) -> bool {
+        let owns_values = function.locals.values().any(|local| matches!(
+            local.ty,
+            Type::Owned { ownership: OwnershipKind::Owned, .. }
+        ));
+        let takes_references = function.basic_blocks.values()
+            .flat_map(|block| &block.statements)
+            .any(|statement| matches!(statement, Statement::Assign { rvalue: Rvalue::Ref { .. }, .. }));
+        !owns_values && !takes_references
+    }
+    
+    /// Index of a self call in tail position in `block`: the last
+    /// assignment, storing into the return local, followed only by storage
+    /// markers on the way to a `Return`
+    fn tail_call_index(function: &Function, block: &BasicBlock) -> Option<usize> {
+        let index = block.statements.iter().rposition(|statement| !is_marker(statement))?;
+        let Statement::Assign { place, rvalue: Rvalue::Call { func, args }, .. } = &block.statements[index] else {
+            return None;
+        };
+        let calls_itself = matches!(
+            func,
+            Operand::Constant(Constant { value: ConstantValue::String(name), .. }) if *name == function.name
+        );
+        if !calls_itself
+            || args.len() != function.parameters.len()
+            || !place.projection.is_empty()
+            || function.return_local != Some(place.local)
+            || !Self::returns_directly(function, &block.terminator)
+        {
+            return None;
+        }
+        Some(index)
+    }
+    
+    /// Whether a terminator reaches `Return` through blocks holding nothing
+    /// but storage markers
+    fn returns_directly(function: &Function, terminator: &Terminator) -> bool {
+        let mut terminator = terminator;
+        let mut visited = HashSet::new();
+        loop {
+            match terminator {
+                Terminator::Return => return true,
+                Terminator::Goto { target } if visited.insert(*target) => {
+                    match function.basic_blocks.get(target) {
+                        Some(block) if block.statements.iter().all(is_marker) => terminator = &block.terminator,
+                        _ => return false,
+                    }
+                }
+                _ => return false,
+            }
+        }
+    }
+    
+    /// Replace the tail call at `index` in `block_id` with assignments to the
+    /// parameters and a jump to `loop_head`
+    fn rewrite_tail_call(function: &mut Function, block_id: BasicBlockId, index: usize, loop_head: BasicBlockId) {
+        let (args, source_info) = match &function.basic_blocks[&block_id].statements[index] {
+            Statement::Assign { rvalue: Rvalue::Call { args, .. }, source_info, .. } => (args.clone(), source_info.clone()),
+            _ => unreachable!("tail call checked by tail_call_index"),
+        };
+        
+        // An argument that reads a parameter must see its value from before
+        // any parameter is reassigned, so such calls go through temporaries
+        let param_locals: HashSet<_> = function.parameters.iter().map(|param| param.local_id).collect();
+        let reads_params = args.iter().any(|arg| {
+            let mut reads = false;
+            arg.clone().map_locals(&mut |local| {
+                reads |= param_locals.contains(&local);
+                local
+            });
+            reads
+        });
+        
+        let mut assignments = Vec::new();
+        let mut values = Vec::new();
+        for (param, arg) in function.parameters.clone().iter().zip(args) {
+            if reads_params {
+                let temp = function.locals.keys().max().map_or(0, |max| max + 1);
+                function.locals.insert(temp, Local {
+                    ty: param.ty.clone(),
+                    is_mutable: false,
+                    source_info: None,
+                });
+                assignments.push(assign(temp, Rvalue::Use(arg), &source_info));
+                values.push(Operand::Copy(Place { local: temp, projection: vec![] }));
+            } else {
+                values.push(arg);
+            }
+        }
+        for (param, value) in function.parameters.iter().zip(values) {
+            assignments.push(assign(param.local_id, Rvalue::Use(value), &source_info));
+        }
+        
+        let block = function.basic_blocks.get_mut(&block_id).unwrap();
+        block.statements.splice(index..=index, assignments);
+        block.terminator = Terminator::Goto { target: loop_head };
+    }
+}
+
+fn is_marker(statement: &Statement) -> bool {
+    matches!(statement, Statement::StorageLive(_) | Statement::StorageDead(_) | Statement::Nop)
+}
+
+fn assign(local: crate::mir::LocalId, rvalue: Rvalue, source_info: &SourceInfo) -> Statement {
+    Statement::Assign {
+        place: Place { local, projection: vec![] },
+        rvalue,
+        source_info: source_info.clone(),
+    }
+}
+
+impl OptimizationPass for TailRecursionEliminationPass {
+    fn name(&self) -> &'static str {
+        "tail-recursion-elimination"
+    }
+    
+    fn run_on_function(&mut self, function: &mut Function) -> Result<bool, SemanticError> {
+        if !Self::can_reuse_frame(function) {
+            return Ok(false);
+        }
+        
+        let mut block_ids: Vec<BasicBlockId> = function.basic_blocks.keys().copied().collect();
+        block_ids.sort();
+        let tail_calls: Vec<(BasicBlockId, usize)> = block_ids.into_iter()
+            .filter_map(|block_id| Self::tail_call_index(function, &function.basic_blocks[&block_id])
+                .map(|index| (block_id, index)))
+            .collect();
+        if tail_calls.is_empty() {
+            return Ok(false);
+        }
+        
+        // Code generation stores the incoming arguments in the entry block,
+        // so the loop jumps back to the old entry under a new one
+        let loop_head = function.entry_block;
+        let entry = function.basic_blocks.keys().max().map_or(0, |max| max + 1);
+        function.basic_blocks.insert(entry, BasicBlock {
+            id: entry,
+            statements: Vec::new(),
+            terminator: Terminator::Goto { target: loop_head },
+        });
+        function.entry_block = entry;
+        
+        for (block_id, index) in tail_calls {
+            Self::rewrite_tail_call(function, block_id, index, loop_head);
+            self.eliminated_calls += 1;
+        }
+        
+        Ok(true)
+    }
+}
+
+impl Default for TailRecursionEliminationPass {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mir::lowering::lower_ast_to_mir;
+    use crate::mir::cfg;
+    
+    fn lower_function(name: &str, source: &str) -> Function {
+        let tokens = crate::lexer::Lexer::new(source, "test.aether".to_string()).tokenize().unwrap();
+        let ast_program = crate::parser::Parser::new(tokens).parse_program().unwrap();
+        let mut program = lower_ast_to_mir(&ast_program).expect("Lowering should succeed");
+        program.functions.remove(name).unwrap()
+    }
+    
+    fn self_calls(function: &Function) -> usize {
+        function.basic_blocks.values()
+            .flat_map(|block| &block.statements)
+            .filter(|statement| matches!(
+                statement,
+                Statement::Assign { rvalue: Rvalue::Call { func: Operand::Constant(Constant { value: ConstantValue::String(name), .. }), .. }, .. }
+                    if *name == function.name
+            ))
+            .count()
+    }
+    
+    #[test]
+    fn test_tail_recursive_accumulate_becomes_a_loop() {
+        let mut function = lower_function("accumulate", r#"
+        (DEFINE_MODULE
+          (NAME 'recursion')
+          (CONTENT
+            (DEFINE_FUNCTION
+              (NAME 'accumulate')
+              (ACCEPTS_PARAMETER (NAME "count") (TYPE INTEGER))
+              (ACCEPTS_PARAMETER (NAME "total") (TYPE INTEGER))
+              (RETURNS INTEGER)
+              (BODY
+                (IF_CONDITION (PREDICATE_LESS_THAN_OR_EQUAL_TO count 0)
+                  (THEN_EXECUTE (RETURN_VALUE total)))
+                (RETURN_VALUE (CALL_FUNCTION 'accumulate'
+                  (ARGUMENTS (EXPRESSION_SUBTRACT count 1) (EXPRESSION_ADD total count))))))))
+        "#);
+        let body_start = function.entry_block;
+        assert_eq!(self_calls(&function), 1);
+        
+        let mut pass = TailRecursionEliminationPass::new();
+        assert!(pass.run_on_function(&mut function).unwrap());
+        assert_eq!(pass.eliminated_calls(), 1);
+        assert_eq!(self_calls(&function), 0);
+        
+        // A fresh entry block leads into the body, which a back edge now
+        // re-enters with the parameters reassigned
+        assert_ne!(function.entry_block, body_start);
+        assert_eq!(function.basic_blocks[&function.entry_block].terminator, Terminator::Goto { target: body_start });
+        let back_edges: Vec<_> = cfg::predecessors(&function, body_start).into_iter()
+            .filter(|pred| *pred != function.entry_block)
+            .collect();
+        assert_eq!(back_edges.len(), 1);
+        let assigned: Vec<_> = function.basic_blocks[&back_edges[0]].statements.iter()
+            .filter_map(|statement| match statement {
+                Statement::Assign { place, .. } => Some(place.local),
+                _ => None,
+            })
+            .collect();
+        for param in &function.parameters {
+            assert!(assigned.contains(&param.local_id));
+        }
+        assert!(crate::mir::validation::Validator::new().validate_function(&function).is_ok());
+    }
+    
+    #[test]
+    fn test_call_that_is_not_in_tail_position_is_kept() {
+        let mut function = lower_function("factorial", r#"
+        (DEFINE_MODULE
+          (NAME 'recursion')
+          (CONTENT
+            (DEFINE_FUNCTION
+              (NAME 'factorial')
+              (ACCEPTS_PARAMETER (NAME "count") (TYPE INTEGER))
+              (RETURNS INTEGER)
+              (BODY
+                (IF_CONDITION (PREDICATE_LESS_THAN_OR_EQUAL_TO count 1)
+                  (THEN_EXECUTE (RETURN_VALUE 1)))
+                (RETURN_VALUE (EXPRESSION_MULTIPLY count
+                  (CALL_FUNCTION 'factorial' (ARGUMENTS (EXPRESSION_SUBTRACT count 1)))))))))
+        "#);
+        
+        assert!(!TailRecursionEliminationPass::new().run_on_function(&mut function).unwrap());
+        assert_eq!(self_calls(&function), 1);
+    }
+}
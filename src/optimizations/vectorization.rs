@@ -701,7 +701,7 @@ impl Default for VectorizationPass {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::mir::{Builder, Statement, Rvalue, Operand, Constant, ConstantValue, Place, SourceInfo};
+    use crate::mir::{Builder, Statement, Rvalue, Operand, Constant, ConstantValue, Place, SourceInfo, CallingConvention, PassControl};
     use crate::types::Type;
     use crate::ast::PrimitiveType;
     use crate::error::SourceLocation;
@@ -769,6 +769,10 @@ mod tests {
             basic_blocks: HashMap::new(),
             entry_block: 0,
             return_local: None,
+            is_pure: false,
+            export_symbol: None,
+            calling_convention: CallingConvention::Rust,
+            pass_control: PassControl::default(),
         };
         
         let width = pass.determine_vector_width(&function, &statements);
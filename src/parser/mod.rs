@@ -81,6 +81,8 @@ pub enum KeywordType {
     AlgorithmHint,
     PerformanceExpectation,
     ComplexityExpectation,
+    Pure,
+    Optimize,
     
     // Performance metric keywords
     LatencyMs,
@@ -116,6 +118,11 @@ pub enum KeywordType {
     ExpressionIntegerDivide,
     ExpressionModulo,
     ExpressionNegate,
+    ExpressionBitwiseAnd,
+    ExpressionBitwiseOr,
+    ExpressionBitwiseXor,
+    ExpressionShiftLeft,
+    ExpressionShiftRight,
     
     // Predicate keywords
     PredicateEquals,
@@ -132,6 +139,7 @@ pub enum KeywordType {
     
     // String operations
     StringConcat,
+    InterpolatedString,
     StringLength,
     StringCharAt,
     Substring,
@@ -140,6 +148,8 @@ pub enum KeywordType {
     
     // Type conversion
     CastToType,
+    SizeOf,
+    AlignOf,
     ToString,
     ToInteger,
     ToFloat,
@@ -150,6 +160,7 @@ pub enum KeywordType {
     ElseIfCondition,
     ElseExecute,
     LoopWhileCondition,
+    LoopWhileMatch,
     LoopForEachElement,
     LoopFixedIterations,
     Counter,
@@ -167,6 +178,8 @@ pub enum KeywordType {
     GetFieldValue,
     GetArrayElement,
     SetArrayElement,
+    Slice,
+    SliceInclusive,
     GetMapValue,
     SetMapValue,
     
@@ -177,6 +190,8 @@ pub enum KeywordType {
     TryExecute,
     CatchException,
     FinallyExecute,
+    PropagateError,
+    Defer,
     
     // Resource management keywords
     ResourceScope,
@@ -194,6 +209,10 @@ pub enum KeywordType {
     Dereference,
     PointerAdd,
     
+    // Ownership conversions
+    Borrow,
+    BorrowMut,
+    
     // FFI keywords
     Library,
     Symbol,
@@ -308,6 +327,11 @@ impl Parser {
             ("EXPRESSION_INTEGER_DIVIDE", KeywordType::ExpressionIntegerDivide),
             ("EXPRESSION_MODULO", KeywordType::ExpressionModulo),
             ("EXPRESSION_NEGATE", KeywordType::ExpressionNegate),
+            ("EXPRESSION_BITWISE_AND", KeywordType::ExpressionBitwiseAnd),
+            ("EXPRESSION_BITWISE_OR", KeywordType::ExpressionBitwiseOr),
+            ("EXPRESSION_BITWISE_XOR", KeywordType::ExpressionBitwiseXor),
+            ("EXPRESSION_SHIFT_LEFT", KeywordType::ExpressionShiftLeft),
+            ("EXPRESSION_SHIFT_RIGHT", KeywordType::ExpressionShiftRight),
             ("PREDICATE_EQUALS", KeywordType::PredicateEquals),
             ("PREDICATE_NOT_EQUALS", KeywordType::PredicateNotEquals),
             ("PREDICATE_LESS_THAN", KeywordType::PredicateLessThan),
@@ -318,12 +342,15 @@ impl Parser {
             ("LOGICAL_OR", KeywordType::LogicalOr),
             ("LOGICAL_NOT", KeywordType::LogicalNot),
             ("STRING_CONCAT", KeywordType::StringConcat),
+            ("INTERPOLATED_STRING", KeywordType::InterpolatedString),
             ("STRING_LENGTH", KeywordType::StringLength),
             ("STRING_CHAR_AT", KeywordType::StringCharAt),
             ("SUBSTRING", KeywordType::Substring),
             ("STRING_EQUALS", KeywordType::StringEquals),
             ("STRING_CONTAINS", KeywordType::StringContains),
             ("CAST_TO_TYPE", KeywordType::CastToType),
+            ("SIZE_OF", KeywordType::SizeOf),
+            ("ALIGN_OF", KeywordType::AlignOf),
             ("TO_STRING", KeywordType::ToString),
             ("TO_INTEGER", KeywordType::ToInteger),
             ("TO_FLOAT", KeywordType::ToFloat),
@@ -332,6 +359,7 @@ impl Parser {
             ("ELSE_IF_CONDITION", KeywordType::ElseIfCondition),
             ("ELSE_EXECUTE", KeywordType::ElseExecute),
             ("LOOP_WHILE_CONDITION", KeywordType::LoopWhileCondition),
+            ("LOOP_WHILE_MATCH", KeywordType::LoopWhileMatch),
             ("LOOP_FOR_EACH_ELEMENT", KeywordType::LoopForEachElement),
             ("LOOP_FIXED_ITERATIONS", KeywordType::LoopFixedIterations),
             ("COUNTER", KeywordType::Counter),
@@ -347,6 +375,8 @@ impl Parser {
             ("GET_FIELD_VALUE", KeywordType::GetFieldValue),
             ("GET_ARRAY_ELEMENT", KeywordType::GetArrayElement),
             ("SET_ARRAY_ELEMENT", KeywordType::SetArrayElement),
+            ("SLICE", KeywordType::Slice),
+            ("SLICE_INCLUSIVE", KeywordType::SliceInclusive),
             ("GET_MAP_VALUE", KeywordType::GetMapValue),
             ("SET_MAP_VALUE", KeywordType::SetMapValue),
             ("EXPRESSION_STATEMENT", KeywordType::ExpressionStatement),
@@ -354,6 +384,8 @@ impl Parser {
             ("CATCH_EXCEPTION", KeywordType::CatchException),
             ("FINALLY_EXECUTE", KeywordType::FinallyExecute),
             ("THROW_EXCEPTION", KeywordType::ThrowException),
+            ("PROPAGATE_ERROR", KeywordType::PropagateError),
+            ("DEFER", KeywordType::Defer),
             ("RESOURCE_SCOPE", KeywordType::ResourceScope),
             ("ACQUIRE_RESOURCE", KeywordType::AcquireResource),
             ("RESOURCE_TYPE", KeywordType::ResourceType),
@@ -367,6 +399,8 @@ impl Parser {
             ("ALGORITHM_HINT", KeywordType::AlgorithmHint),
             ("PERFORMANCE_EXPECTATION", KeywordType::PerformanceExpectation),
             ("COMPLEXITY_EXPECTATION", KeywordType::ComplexityExpectation),
+            ("PURE", KeywordType::Pure),
+            ("OPTIMIZE", KeywordType::Optimize),
             ("LIBRARY", KeywordType::Library),
             ("SYMBOL", KeywordType::Symbol),
             ("CALLING_CONVENTION", KeywordType::CallingConvention),
@@ -414,6 +448,8 @@ impl Parser {
             ("ADDRESS_OF", KeywordType::AddressOf),
             ("DEREFERENCE", KeywordType::Dereference),
             ("POINTER_ADD", KeywordType::PointerAdd),
+            ("BORROW", KeywordType::Borrow),
+            ("BORROW_MUT", KeywordType::BorrowMut),
             // Metadata keywords
             ("PRECONDITION", KeywordType::Precondition),
             ("POSTCONDITION", KeywordType::Postcondition),
@@ -473,6 +509,15 @@ impl Parser {
         }
     }
 
+    /// Location of the most recently consumed non-comment token
+    fn previous_token_location(&self) -> Option<&SourceLocation> {
+        self.tokens[..self.position.min(self.tokens.len())]
+            .iter()
+            .rev()
+            .find(|token| !matches!(token.token_type, TokenType::Comment(_)))
+            .map(|token| &token.location)
+    }
+
     /// Check if we're at the end of tokens
     fn is_at_end(&self) -> bool {
         match self.current_token() {
@@ -727,34 +772,20 @@ impl Parser {
         self.recovery_mode = true;
     }
 
-    /// Synchronize to the next recovery point (typically a closing parenthesis)
-    fn synchronize(&mut self) {
+    /// Skip the parenthesized item that starts at `item_start`, so parsing
+    /// resumes at the next item rather than partway through the broken one
+    fn synchronize(&mut self, item_start: usize) {
+        self.position = item_start;
         let mut paren_depth = 0;
-        let start_position = self.position;
         
-        while !self.is_at_end() {
-            if let Some(token) = self.current_token() {
-                match &token.token_type {
-                    TokenType::LeftParen => {
-                        paren_depth += 1;
-                    }
-                    TokenType::RightParen => {
-                        if paren_depth == 0 {
-                            // Found a closing paren at the same level, consume it and exit
-                            self.advance();
-                            self.recovery_mode = false;
-                            return;
-                        }
-                        paren_depth -= 1;
-                    }
-                    _ => {}
-                }
+        while let Some(token) = self.current_token() {
+            match &token.token_type {
+                TokenType::LeftParen => paren_depth += 1,
+                TokenType::RightParen => paren_depth -= 1,
+                _ => {}
             }
             self.advance();
-            
-            // Prevent infinite loops - if we've advanced more than 100 tokens, stop
-            if self.position > start_position && self.position - start_position > 100 {
-                eprintln!("Error recovery: Exceeded maximum token lookahead");
+            if paren_depth <= 0 {
                 break;
             }
         }
@@ -773,6 +804,17 @@ impl Parser {
 
     /// Parse a complete program (collection of modules)
     pub fn parse_program(&mut self) -> Result<Program, ParserError> {
+        let (program, errors) = self.parse_program_recovering();
+        match errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(program),
+        }
+    }
+
+    /// Parse a complete program, skipping module items that fail to parse.
+    /// Returns whatever parsed along with every syntax error, so tooling can
+    /// still analyze the rest of a file that does not parse.
+    pub fn parse_program_recovering(&mut self) -> (Program, Vec<ParserError>) {
         let start_location = self.current_token()
             .map(|t| t.location.clone())
             .unwrap_or_else(SourceLocation::unknown);
@@ -788,25 +830,22 @@ impl Parser {
                 }
             }
             
+            let module_start = self.position;
             match self.parse_module() {
                 Ok(module) => modules.push(module),
                 Err(error) => {
                     self.record_error(error);
-                    self.synchronize();
+                    self.synchronize(module_start);
                     // Continue parsing after error
                 }
             }
         }
 
-        // If we have errors, return the first one
-        if self.has_errors() {
-            return Err(self.errors[0].clone());
-        }
-
-        Ok(Program {
+        let program = Program {
             modules,
             source_location: start_location,
-        })
+        };
+        (program, self.errors.clone())
     }
 
     /// Parse a module definition
@@ -865,6 +904,7 @@ impl Parser {
                                 // Parse each content item
                                 if let Some(content_token) = self.current_token() {
                                     if matches!(content_token.token_type, TokenType::LeftParen) {
+                                        let item_start = self.position;
                                         match self.parse_module_content_item() {
                                             Ok(content_item) => {
                                                 match content_item {
@@ -886,7 +926,7 @@ impl Parser {
                                                 // Record error and synchronize
                                                 eprintln!("Parser: Error parsing module content: {:?}", error);
                                                 self.record_error(error);
-                                                self.synchronize();
+                                                self.synchronize(item_start);
                                             }
                                         }
                                     } else {
@@ -1368,6 +1408,7 @@ impl Parser {
         let mut name = None;
         let mut intent = None;
         let mut variants = Vec::new();
+        let mut has_variants = false;
         let mut generic_parameters = Vec::new();
         
         // Parse enum fields
@@ -1397,8 +1438,10 @@ impl Parser {
                         }
                         Some(KeywordType::Variants) => {
                             self.advance(); // consume VARIANTS keyword
+                            has_variants = true;
                             
-                            // Parse variant list
+                            // Parse variant list; an empty list declares a
+                            // type without values
                             while let Some(token) = self.current_token() {
                                 if matches!(token.token_type, TokenType::RightParen) {
                                     break;
@@ -1495,7 +1538,7 @@ impl Parser {
             location: start_location.clone(),
         })?;
         
-        if variants.is_empty() {
+        if !has_variants {
             return Err(ParserError::MissingRequiredField {
                 field: "VARIANTS".to_string(),
                 construct: "DEFINE_ENUMERATION_TYPE".to_string(),
@@ -1754,6 +1797,7 @@ impl Parser {
         let mut parameters = Vec::new();
         let mut return_type = None;
         let mut body = None;
+        let mut export_info = None;
         let mut metadata = FunctionMetadata {
             preconditions: Vec::new(),
             postconditions: Vec::new(),
@@ -1764,6 +1808,8 @@ impl Parser {
             throws_exceptions: Vec::new(),
             thread_safe: None,
             may_block: None,
+            pure: None,
+            optimization_hints: vec![],
         };
         
         // Parse function fields
@@ -1844,6 +1890,37 @@ impl Parser {
                             self.advance(); // consume MAY_BLOCK
                             metadata.may_block = Some(self.consume_boolean()?);
                         }
+                        Some(KeywordType::Pure) => {
+                            self.advance(); // consume PURE
+                            metadata.pure = Some(self.consume_boolean()?);
+                        }
+                        Some(KeywordType::Optimize) => {
+                            self.advance(); // consume OPTIMIZE
+                            let hint = self.consume_identifier()?;
+                            metadata.optimization_hints.push(match hint.name.as_str() {
+                                "NONE" => OptimizationHint::None,
+                                "NO_INLINE" => OptimizationHint::NoInline,
+                                "INLINE_ALWAYS" => OptimizationHint::InlineAlways,
+                                "COLD" => OptimizationHint::Cold,
+                                "HOT" => OptimizationHint::Hot,
+                                _ => {
+                                    return Err(ParserError::UnexpectedToken {
+                                        found: hint.name,
+                                        expected: "optimization hint (NONE, NO_INLINE, INLINE_ALWAYS, COLD, HOT)".to_string(),
+                                        location: hint.source_location,
+                                    });
+                                }
+                            });
+                        }
+                        Some(KeywordType::ExportAs) => {
+                            self.advance(); // consume EXPORT_AS
+                            export_info = Some(ExportInfo {
+                                export_type: ExportType::CFunction,
+                                symbol_name: Some(self.consume_string()?),
+                                calling_convention: Some(CallingConvention::C),
+                                package_name: None,
+                            });
+                        }
                         _ => {
                             return Err(ParserError::UnexpectedToken {
                                 found: keyword.clone(),
@@ -1890,7 +1967,7 @@ impl Parser {
             return_type: Box::new(return_type),
             metadata,
             body,
-            export_info: None,
+            export_info,
             source_location: start_location,
         })
     }
@@ -2208,9 +2285,15 @@ impl Parser {
         match &token.token_type {
             TokenType::Integer(value) => {
                 let int_value = *value;
+                let suffix = match token.integer_suffix() {
+                    Some("i32") => Some(PrimitiveType::Integer32),
+                    Some("i64") => Some(PrimitiveType::Integer64),
+                    _ => None,
+                };
                 self.advance();
                 Ok(Expression::IntegerLiteral {
                     value: int_value,
+                    suffix,
                     source_location: location,
                 })
             }
@@ -2361,6 +2444,61 @@ impl Parser {
                             source_location: start_location,
                         })
                     }
+                    Some(KeywordType::ExpressionBitwiseAnd) => {
+                        self.advance(); // consume EXPRESSION_BITWISE_AND
+                        let left = Box::new(self.parse_expression()?);
+                        let right = Box::new(self.parse_expression()?);
+                        self.consume_right_paren()?;
+                        Ok(Expression::BitwiseAnd {
+                            left,
+                            right,
+                            source_location: start_location,
+                        })
+                    }
+                    Some(KeywordType::ExpressionBitwiseOr) => {
+                        self.advance(); // consume EXPRESSION_BITWISE_OR
+                        let left = Box::new(self.parse_expression()?);
+                        let right = Box::new(self.parse_expression()?);
+                        self.consume_right_paren()?;
+                        Ok(Expression::BitwiseOr {
+                            left,
+                            right,
+                            source_location: start_location,
+                        })
+                    }
+                    Some(KeywordType::ExpressionBitwiseXor) => {
+                        self.advance(); // consume EXPRESSION_BITWISE_XOR
+                        let left = Box::new(self.parse_expression()?);
+                        let right = Box::new(self.parse_expression()?);
+                        self.consume_right_paren()?;
+                        Ok(Expression::BitwiseXor {
+                            left,
+                            right,
+                            source_location: start_location,
+                        })
+                    }
+                    Some(KeywordType::ExpressionShiftLeft) => {
+                        self.advance(); // consume EXPRESSION_SHIFT_LEFT
+                        let left = Box::new(self.parse_expression()?);
+                        let right = Box::new(self.parse_expression()?);
+                        self.consume_right_paren()?;
+                        Ok(Expression::ShiftLeft {
+                            left,
+                            right,
+                            source_location: start_location,
+                        })
+                    }
+                    Some(KeywordType::ExpressionShiftRight) => {
+                        self.advance(); // consume EXPRESSION_SHIFT_RIGHT
+                        let left = Box::new(self.parse_expression()?);
+                        let right = Box::new(self.parse_expression()?);
+                        self.consume_right_paren()?;
+                        Ok(Expression::ShiftRight {
+                            left,
+                            right,
+                            source_location: start_location,
+                        })
+                    }
                     Some(KeywordType::CallFunction) => {
                         self.advance(); // consume CALL_FUNCTION
                         self.parse_function_call_expression(start_location)
@@ -2442,6 +2580,28 @@ impl Parser {
                             source_location: start_location,
                         })
                     }
+                    Some(KeywordType::InterpolatedString) => {
+                        self.advance(); // consume INTERPOLATED_STRING
+                        let token = self.current_token().ok_or_else(|| ParserError::UnexpectedEof {
+                            expected: "string literal".to_string(),
+                        })?;
+                        let template = match &token.token_type {
+                            TokenType::String(value) => value.clone(),
+                            _ => {
+                                return Err(ParserError::UnexpectedToken {
+                                    found: format!("{:?}", token.token_type),
+                                    expected: "string literal".to_string(),
+                                    location: token.location.clone(),
+                                });
+                            }
+                        };
+                        self.advance();
+                        self.consume_right_paren()?;
+                        Ok(Expression::InterpolatedString {
+                            parts: Self::parse_interpolated_parts(&template, &start_location)?,
+                            source_location: start_location,
+                        })
+                    }
                     Some(KeywordType::StringLength) => {
                         self.advance(); // consume STRING_LENGTH
                         let string = Box::new(self.parse_expression()?);
@@ -2539,6 +2699,21 @@ impl Parser {
                             source_location: start_location,
                         })
                     }
+                    Some(KeywordType::Slice) | Some(KeywordType::SliceInclusive) => {
+                        let inclusive = matches!(self.keywords.get(&keyword), Some(KeywordType::SliceInclusive));
+                        self.advance(); // consume SLICE or SLICE_INCLUSIVE
+                        let collection = Box::new(self.parse_expression()?);
+                        let start = Box::new(self.parse_expression()?);
+                        let end = Box::new(self.parse_expression()?);
+                        self.consume_right_paren()?;
+                        Ok(Expression::Slice {
+                            collection,
+                            start,
+                            end,
+                            inclusive,
+                            source_location: start_location,
+                        })
+                    }
                     Some(KeywordType::ArrayLength) => {
                         self.advance(); // consume ARRAY_LENGTH
                         let array = Box::new(self.parse_expression()?);
@@ -2654,6 +2829,15 @@ impl Parser {
                             source_location: start_location,
                         })
                     }
+                    Some(KeywordType::PropagateError) => {
+                        self.advance(); // consume PROPAGATE_ERROR
+                        let value = Box::new(self.parse_expression()?);
+                        self.consume_right_paren()?;
+                        Ok(Expression::Try {
+                            value,
+                            source_location: start_location,
+                        })
+                    }
                     Some(KeywordType::ToString) => {
                         self.advance(); // consume TO_STRING
                         let value = Box::new(self.parse_expression()?);
@@ -2754,6 +2938,24 @@ impl Parser {
                             source_location: start_location,
                         })
                     }
+                    Some(KeywordType::SizeOf) => {
+                        self.advance(); // consume SIZE_OF
+                        let target_type = Box::new(self.parse_type_specifier()?);
+                        self.consume_right_paren()?;
+                        Ok(Expression::SizeOf {
+                            target_type,
+                            source_location: start_location,
+                        })
+                    }
+                    Some(KeywordType::AlignOf) => {
+                        self.advance(); // consume ALIGN_OF
+                        let target_type = Box::new(self.parse_type_specifier()?);
+                        self.consume_right_paren()?;
+                        Ok(Expression::AlignOf {
+                            target_type,
+                            source_location: start_location,
+                        })
+                    }
                     Some(KeywordType::AddressOf) => {
                         self.advance(); // consume ADDRESS_OF
                         let operand = Box::new(self.parse_expression()?);
@@ -2763,6 +2965,17 @@ impl Parser {
                             source_location: start_location,
                         })
                     }
+                    Some(KeywordType::Borrow) | Some(KeywordType::BorrowMut) => {
+                        let mutable = matches!(self.keywords.get(&keyword), Some(KeywordType::BorrowMut));
+                        self.advance(); // consume BORROW or BORROW_MUT
+                        let value = Box::new(self.parse_expression()?);
+                        self.consume_right_paren()?;
+                        Ok(Expression::Borrow {
+                            value,
+                            mutable,
+                            source_location: start_location,
+                        })
+                    }
                     Some(KeywordType::Dereference) => {
                         self.advance(); // consume DEREFERENCE
                         let pointer = Box::new(self.parse_expression()?);
@@ -2849,7 +3062,15 @@ impl Parser {
             }
         };
 
-        result
+        // Widen the expression's location to cover everything through the
+        // closing parenthesis
+        result.map(|mut expr| {
+            if let Some(end) = self.previous_token_location() {
+                let span = SourceLocation::span(expr.source_location(), end);
+                *expr.source_location_mut() = span;
+            }
+            expr
+        })
     }
 
     /// Parse a function call expression
@@ -2916,9 +3137,7 @@ impl Parser {
                             
                             return Ok(Expression::FunctionCall {
                                 call: FunctionCall {
-                                    function_reference: FunctionReference::Local {
-                                        name: function_name,
-                                    },
+                                    function_reference: FunctionReference::from_path(function_name),
                                     arguments,
                                     variadic_arguments: Vec::new(),
                                 },
@@ -2954,9 +3173,7 @@ impl Parser {
                             
                             return Ok(Expression::FunctionCall {
                                 call: FunctionCall {
-                                    function_reference: FunctionReference::Local {
-                                        name: function_name,
-                                    },
+                                    function_reference: FunctionReference::from_path(function_name),
                                     arguments,
                                     variadic_arguments: Vec::new(),
                                 },
@@ -3021,9 +3238,7 @@ impl Parser {
         
         Ok(Expression::FunctionCall {
             call: FunctionCall {
-                function_reference: FunctionReference::Local {
-                    name: function_name,
-                },
+                function_reference: FunctionReference::from_path(function_name),
                 arguments,
                 variadic_arguments: Vec::new(),
             },
@@ -3100,6 +3315,10 @@ impl Parser {
                         self.advance();
                         self.parse_while_loop(location)
                     }
+                    Some(KeywordType::LoopWhileMatch) => {
+                        self.advance();
+                        self.parse_while_match_loop(location)
+                    }
                     Some(KeywordType::LoopForEachElement) => {
                         self.advance();
                         self.parse_for_each_loop(location)
@@ -3142,6 +3361,12 @@ impl Parser {
                         self.consume_right_paren()?;
                         Ok(Statement::Throw { exception, source_location: location })
                     }
+                    Some(KeywordType::Defer) => {
+                        self.advance();
+                        let expression = Box::new(self.parse_expression()?);
+                        self.consume_right_paren()?;
+                        Ok(Statement::Defer { expression, source_location: location })
+                    }
                     Some(KeywordType::ResourceScope) => {
                         self.advance();
                         let scope = self.parse_resource_scope(location.clone())?;
@@ -3490,6 +3715,28 @@ impl Parser {
         })
     }
     
+    /// Parse a loop that runs while a value matches a pattern:
+    /// `(LOOP_WHILE_MATCH value (Variant binding) (ITERATION_BODY ...))`
+    fn parse_while_match_loop(&mut self, start_location: SourceLocation) -> Result<Statement, ParserError> {
+        let value = Box::new(self.parse_expression()?);
+        let pattern = self.parse_pattern()?;
+        
+        self.consume_left_paren()?;
+        self.consume_keyword(KeywordType::IterationBody)?;
+        let body = self.parse_block()?;
+        self.consume_right_paren()?;
+        
+        self.consume_right_paren()?;
+        
+        Ok(Statement::WhileMatchLoop {
+            value,
+            pattern,
+            body,
+            label: None,
+            source_location: start_location,
+        })
+    }
+    
     /// Parse for-each loop
     fn parse_for_each_loop(&mut self, start_location: SourceLocation) -> Result<Statement, ParserError> {
         // LOOP_FOR_EACH_ELEMENT has already been consumed
@@ -3805,9 +4052,7 @@ impl Parser {
         let function_name = self.consume_identifier()?;
         
         // For now, assume all function calls are local
-        let function_reference = FunctionReference::Local {
-            name: function_name,
-        };
+        let function_reference = FunctionReference::from_path(function_name);
         
         // Parse arguments - for now, all arguments are treated as variadic
         // since we don't have proper parameter name parsing yet
@@ -3859,16 +4104,10 @@ impl Parser {
             self.consume_right_paren()?; // Close case
         }
         
+        // A match without cases is left for semantic analysis, which only
+        // accepts it over a type without values
         self.consume_right_paren()?; // Close match expression
         
-        if cases.is_empty() {
-            return Err(ParserError::MissingRequiredField {
-                field: "CASE".to_string(),
-                construct: "MATCH_EXPRESSION".to_string(),
-                location: start_location.clone(),
-            });
-        }
-        
         Ok(Expression::Match {
             value,
             cases,
@@ -3946,6 +4185,21 @@ impl Parser {
                     source_location: first_token.location.clone(),
                 })
             }
+            TokenType::Keyword(keyword) if keyword == "INTEGER_RANGE" || keyword == "INTEGER_RANGE_INCLUSIVE" => {
+                // Range pattern
+                let inclusive = keyword == "INTEGER_RANGE_INCLUSIVE";
+                self.advance(); // consume INTEGER_RANGE / INTEGER_RANGE_INCLUSIVE
+                let start = Box::new(self.parse_expression()?);
+                let end = Box::new(self.parse_expression()?);
+                self.consume_right_paren()?;
+                
+                Ok(Pattern::Range {
+                    start,
+                    end,
+                    inclusive,
+                    source_location: first_token.location.clone(),
+                })
+            }
             _ => {
                 // Literal pattern
                 let start_loc = first_token.location.clone();
@@ -3959,6 +4213,66 @@ impl Parser {
             }
         }
     }
+
+    /// Split an interpolation template into literal text and embedded
+    /// expressions. `{expr}` embeds an expression; `{{` and `}}` stand for
+    /// literal braces.
+    fn parse_interpolated_parts(
+        template: &str,
+        location: &SourceLocation,
+    ) -> Result<Vec<InterpolatedPart>, ParserError> {
+        let malformed = |reason: &str| ParserError::MalformedSExpression {
+            reason: format!("INTERPOLATED_STRING {}", reason),
+            location: location.clone(),
+        };
+
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut source = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => source.push(c),
+                            None => return Err(malformed("has an unclosed '{'")),
+                        }
+                    }
+
+                    let mut lexer = crate::lexer::Lexer::new(&source, location.file.clone());
+                    let tokens = lexer
+                        .tokenize()
+                        .map_err(|e| malformed(&format!("embeds an invalid expression: {}", e)))?;
+                    let mut parser = Parser::new(tokens);
+                    let expression = parser.parse_expression()?;
+                    if !parser.is_at_end() {
+                        return Err(malformed("embeds more than one expression in a single '{}'"));
+                    }
+
+                    if !literal.is_empty() {
+                        parts.push(InterpolatedPart::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(InterpolatedPart::Expression(Box::new(expression)));
+                }
+                '}' => return Err(malformed("has an unmatched '}'; write '}}' for a literal brace")),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(InterpolatedPart::Literal(literal));
+        }
+        Ok(parts)
+    }
 }
 
 /// Module content items
@@ -4070,6 +4384,186 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_interpolated_string_parsing() {
+        let source = r#"(INTERPOLATED_STRING "{{x}} = {x}")"#;
+        let mut lexer = Lexer::new(source, "test.aether".to_string());
+        let tokens = lexer.tokenize().unwrap();
+        let mut parser = Parser::new(tokens);
+
+        let expression = parser.parse_expression().unwrap();
+        let Expression::InterpolatedString { parts, .. } = expression else {
+            panic!("Expected InterpolatedString, got {:?}", expression);
+        };
+        assert_eq!(parts.len(), 2);
+        assert!(matches!(&parts[0], InterpolatedPart::Literal(text) if text == "{x} = "));
+        assert!(matches!(&parts[1], InterpolatedPart::Expression(expr)
+            if matches!(expr.as_ref(), Expression::Variable { name, .. } if name.name == "x")));
+
+        let mut lexer = Lexer::new(r#"(INTERPOLATED_STRING "x}")"#, "test.aether".to_string());
+        let mut parser = Parser::new(lexer.tokenize().unwrap());
+        assert!(parser.parse_expression().is_err());
+    }
+
+    #[test]
+    fn test_type_layout_parsing() {
+        let parse = |source: &str| {
+            let mut lexer = Lexer::new(source, "test.aether".to_string());
+            Parser::new(lexer.tokenize().unwrap()).parse_expression().unwrap()
+        };
+
+        assert!(matches!(parse("(SIZE_OF INTEGER)"), Expression::SizeOf { target_type, .. }
+            if matches!(target_type.as_ref(), TypeSpecifier::Primitive { type_name: PrimitiveType::Integer, .. })));
+        assert!(matches!(parse("(ALIGN_OF Point)"), Expression::AlignOf { target_type, .. }
+            if matches!(target_type.as_ref(), TypeSpecifier::Named { name, .. } if name.name == "Point")));
+    }
+
+    #[test]
+    fn test_binary_expression_span_covers_operands() {
+        let source = "(DEFINE_MODULE (NAME 'span_test') (CONTENT (DECLARE_CONSTANT (NAME 'total') (TYPE INTEGER) (VALUE (EXPRESSION_ADD (EXPRESSION_MULTIPLY 2 3) 40)))))";
+
+        let mut lexer = Lexer::new(source, "test.aether".to_string());
+        let tokens = lexer.tokenize().unwrap();
+        let mut parser = Parser::new(tokens);
+        let program = parser.parse_program().unwrap();
+
+        let value = program.modules[0].constant_declarations[0].value.as_ref();
+        let (left, right) = match value {
+            Expression::Add { left, right, .. } => (left, right),
+            other => panic!("Expected Add expression, got {:?}", other),
+        };
+
+        let span = value.source_location();
+        assert!(span.contains(left.source_location()));
+        assert!(span.contains(right.source_location()));
+
+        // The span runs from the opening parenthesis through the closing one
+        let add_text = "(EXPRESSION_ADD (EXPRESSION_MULTIPLY 2 3) 40)";
+        let start = source.find(add_text).unwrap();
+        assert_eq!(span.offset, start);
+        assert_eq!(span.end_offset, start + add_text.len());
+        assert_eq!(&source[left.source_location().offset..left.source_location().end_offset], "(EXPRESSION_MULTIPLY 2 3)");
+        assert_eq!(&source[right.source_location().offset..right.source_location().end_offset], "40");
+    }
+
+    #[test]
+    fn test_range_pattern_parsing() {
+        let source = "(DEFINE_MODULE (NAME 'ranges') (CONTENT (DECLARE_CONSTANT (NAME 'bucket') (TYPE INTEGER) \
+            (VALUE (MATCH_EXPRESSION 42 (CASE (INTEGER_RANGE 0 10) 1) (CASE (INTEGER_RANGE_INCLUSIVE 10 99) 2) (CASE (_) 3))))))";
+
+        let mut lexer = Lexer::new(source, "test.aether".to_string());
+        let tokens = lexer.tokenize().unwrap();
+        let mut parser = Parser::new(tokens);
+        let program = parser.parse_program().unwrap();
+
+        let cases = match program.modules[0].constant_declarations[0].value.as_ref() {
+            Expression::Match { cases, .. } => cases,
+            other => panic!("Expected Match expression, got {:?}", other),
+        };
+        assert!(matches!(cases[0].pattern, Pattern::Range { inclusive: false, .. }));
+        assert!(matches!(&cases[1].pattern, Pattern::Range { start, inclusive: true, .. }
+            if matches!(start.as_ref(), Expression::IntegerLiteral { value: 10, .. })));
+        assert!(matches!(cases[2].pattern, Pattern::Wildcard { .. }));
+    }
+
+    #[test]
+    fn test_while_match_loop_parsing() {
+        let source = "(DEFINE_MODULE (NAME 'loops') (CONTENT (DEFINE_FUNCTION (NAME 'drain') \
+            (ACCEPTS_PARAMETER (NAME \"queue\") (TYPE Outcome)) (RETURNS INTEGER) \
+            (BODY (LOOP_WHILE_MATCH queue (Ok item) (ITERATION_BODY (RETURN_VALUE item))) (RETURN_VALUE 0)))))";
+
+        let mut lexer = Lexer::new(source, "test.aether".to_string());
+        let tokens = lexer.tokenize().unwrap();
+        let mut parser = Parser::new(tokens);
+        let program = parser.parse_program().unwrap();
+
+        let body = &program.modules[0].function_definitions[0].body;
+        match &body.statements[0] {
+            Statement::WhileMatchLoop { value, pattern, body, .. } => {
+                assert!(matches!(value.as_ref(), Expression::Variable { name, .. } if name.name == "queue"));
+                assert!(matches!(pattern, Pattern::EnumVariant { variant_name, binding: Some(binding), .. }
+                    if variant_name.name == "Ok" && binding.name == "item"));
+                assert_eq!(body.statements.len(), 1);
+            }
+            other => panic!("Expected while-match loop, got {:?}", other),
+        }
+        assert_eq!(body.statements.len(), 2);
+    }
+
+    #[test]
+    fn test_optimize_hint_parsing() {
+        let source = "(DEFINE_MODULE (NAME 'hints') (CONTENT (DEFINE_FUNCTION (NAME 'helper') \
+            (OPTIMIZE NO_INLINE) (OPTIMIZE NONE) (OPTIMIZE INLINE_ALWAYS) (OPTIMIZE COLD) (OPTIMIZE HOT) \
+            (RETURNS INTEGER) (BODY (RETURN_VALUE 0)))))";
+
+        let mut lexer = Lexer::new(source, "test.aether".to_string());
+        let tokens = lexer.tokenize().unwrap();
+        let mut parser = Parser::new(tokens);
+        let program = parser.parse_program().unwrap();
+
+        let metadata = &program.modules[0].function_definitions[0].metadata;
+        assert_eq!(metadata.optimization_hints, vec![
+            OptimizationHint::NoInline,
+            OptimizationHint::None,
+            OptimizationHint::InlineAlways,
+            OptimizationHint::Cold,
+            OptimizationHint::Hot,
+        ]);
+    }
+
+    #[test]
+    fn test_defer_parsing() {
+        let source = "(DEFINE_MODULE (NAME 'cleanup') (CONTENT (DEFINE_FUNCTION (NAME 'run') \
+            (RETURNS INTEGER) (BODY (DEFER (CALL_FUNCTION close)) (RETURN_VALUE 0)))))";
+
+        let mut lexer = Lexer::new(source, "test.aether".to_string());
+        let tokens = lexer.tokenize().unwrap();
+        let mut parser = Parser::new(tokens);
+        let program = parser.parse_program().unwrap();
+
+        let body = &program.modules[0].function_definitions[0].body;
+        assert!(matches!(&body.statements[0], Statement::Defer { expression, .. }
+            if matches!(expression.as_ref(), Expression::FunctionCall { .. })));
+    }
+
+    #[test]
+    fn test_recovering_parse_skips_broken_items() {
+        let source = "(DEFINE_MODULE (NAME 'partial') (CONTENT \
+            (DEFINE_FUNCTION (NAME 42) (RETURNS INTEGER) (BODY (RETURN_VALUE (EXPRESSION_ADD 1 2)))) \
+            (DEFINE_FUNCTION (NAME 'valid') (RETURNS INTEGER) (BODY (RETURN_VALUE 1)))))";
+
+        let mut lexer = Lexer::new(source, "test.aether".to_string());
+        let tokens = lexer.tokenize().unwrap();
+        let (program, errors) = Parser::new(tokens.clone()).parse_program_recovering();
+
+        assert_eq!(errors.len(), 1);
+        let functions = &program.modules[0].function_definitions;
+        assert_eq!(functions.len(), 1);
+        assert_eq!(functions[0].name.name, "valid");
+
+        // The strict entry point still fails on the first error
+        assert!(Parser::new(tokens).parse_program().is_err());
+    }
+
+    #[test]
+    fn test_integer_literal_suffix_parsing() {
+        let source = "(DEFINE_MODULE (NAME 'masks') (CONTENT \
+            (DECLARE_CONSTANT (NAME 'low') (TYPE INTEGER) (VALUE 0xFF)) \
+            (DECLARE_CONSTANT (NAME 'wide') (TYPE INTEGER) (VALUE 0b1010i64))))";
+
+        let mut lexer = Lexer::new(source, "test.aether".to_string());
+        let tokens = lexer.tokenize().unwrap();
+        let mut parser = Parser::new(tokens);
+        let program = parser.parse_program().unwrap();
+
+        let constants = &program.modules[0].constant_declarations;
+        assert!(matches!(constants[0].value.as_ref(), Expression::IntegerLiteral { value: 255, suffix: None, .. }));
+        assert!(matches!(
+            constants[1].value.as_ref(),
+            Expression::IntegerLiteral { value: 10, suffix: Some(PrimitiveType::Integer64), .. }
+        ));
+    }
+
     #[test]
     fn test_parser_error_handling() {
         let tokens = vec![
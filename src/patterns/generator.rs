@@ -478,6 +478,7 @@ impl PatternGenerator {
         // Simplified expression parsing
         Ok(Expression::IntegerLiteral {
             value: 0,
+            suffix: None,
             source_location: SourceLocation::unknown(),
         })
     }
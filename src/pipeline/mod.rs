@@ -63,6 +63,14 @@ pub struct CompileOptions {
     pub syntax_only: bool,
     /// Compile as a library (shared object/dylib)
     pub compile_as_library: bool,
+    /// Emit runtime bounds checks for slices and other range operations
+    pub bounds_checks: bool,
+    /// Whether violated contracts abort or log a warning and continue
+    pub contract_mode: mir::lowering::ContractMode,
+    /// Whether contract failure messages name where the contract was declared
+    pub contract_locations: bool,
+    /// Whether string lengths and indices count bytes or code points
+    pub string_indexing: mir::lowering::StringIndexing,
 }
 
 impl Default for CompileOptions {
@@ -81,6 +89,10 @@ impl Default for CompileOptions {
             emit_object_only: false,
             syntax_only: false,
             compile_as_library: false,
+            bounds_checks: true,
+            contract_mode: mir::lowering::ContractMode::Trap,
+            contract_locations: true,
+            string_indexing: mir::lowering::StringIndexing::Bytes,
         }
     }
 }
@@ -282,9 +294,19 @@ impl CompilationPipeline {
                 }
             }
             
-            mir::lowering::lower_ast_to_mir_with_symbols(&program, symbol_table)?
+            let mut lowering = mir::lowering::LoweringContext::with_symbol_table(symbol_table);
+            lowering.set_bounds_checks(self.options.bounds_checks);
+            lowering.set_contract_mode(self.options.contract_mode);
+            lowering.set_contract_locations(self.options.contract_locations);
+            lowering.set_string_indexing(self.options.string_indexing);
+            lowering.lower_program(&program)?
         };
         
+        if !self.options.compile_as_library {
+            mir_program.validate_entry_point()?;
+        }
+        mir_program.validate_purity()?;
+        
         stats.phase_times.insert("mir_generation".to_string(), mir_start.elapsed().as_millis());
         
         if self.options.enable_profiling {
@@ -340,7 +340,8 @@ impl ResourceAnalyzer {
                 }
             }
             
-            Statement::WhileLoop { condition, body, .. } => {
+            Statement::WhileLoop { condition, body, .. }
+            | Statement::WhileMatchLoop { value: condition, body, .. } => {
                 self.check_resource_usage_in_expression(condition)?;
                 self.analyze_block(body)?;
             }
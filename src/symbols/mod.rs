@@ -18,7 +18,7 @@
 
 use crate::types::{Type, TypeDefinition};
 use crate::error::{SemanticError, SourceLocation};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Symbol information
 #[derive(Debug, Clone)]
@@ -272,6 +272,12 @@ impl SymbolTable {
         self.type_definitions.get(name)
     }
     
+    /// Look up a type definition, also trying the module-qualified and bare
+    /// forms of the name
+    pub fn lookup_type_definition_in(&self, name: &str, module: Option<&str>) -> Option<&TypeDefinition> {
+        crate::types::find_type_definition(&self.type_definitions, name, module)
+    }
+    
     /// Get all type definitions
     pub fn get_type_definitions(&self) -> &HashMap<String, TypeDefinition> {
         &self.type_definitions
@@ -315,6 +321,49 @@ impl SymbolTable {
         })
     }
     
+    /// Names of the variables in scope that have not been initialized yet
+    pub fn uninitialized_variables(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        let mut seen = HashSet::new();
+        let mut current = Some(self.current_scope);
+        
+        while let Some(scope) = current {
+            for (name, symbol) in &self.scopes[scope].symbols {
+                // Inner scopes shadow outer ones
+                if seen.insert(name.clone()) && !symbol.is_initialized {
+                    names.push(name.clone());
+                }
+            }
+            current = self.scopes[scope].parent;
+        }
+        
+        names
+    }
+    
+    /// Mark a variable as not initialized, for analyses that undo an
+    /// assignment made on only one path
+    pub fn mark_variable_uninitialized(&mut self, name: &str) -> Result<(), SemanticError> {
+        let mut current = self.current_scope;
+        
+        loop {
+            if let Some(symbol) = self.scopes[current].symbols.get_mut(name) {
+                symbol.is_initialized = false;
+                return Ok(());
+            }
+            
+            if let Some(parent) = self.scopes[current].parent {
+                current = parent;
+            } else {
+                break;
+            }
+        }
+        
+        Err(SemanticError::UndefinedSymbol {
+            symbol: name.to_string(),
+            location: SourceLocation::unknown(),
+        })
+    }
+    
     /// Check if a variable is mutable
     pub fn is_variable_mutable(&self, name: &str) -> Result<bool, SemanticError> {
         if let Some(symbol) = self.lookup_symbol(name) {
@@ -360,6 +409,14 @@ impl SymbolTable {
         symbols
     }
     
+    /// Get all symbols declared at global or module level, including those
+    /// of modules whose scope has already been exited
+    pub fn module_level_symbols(&self) -> impl Iterator<Item = &Symbol> {
+        self.scopes.iter()
+            .filter(|scope| matches!(scope.kind, ScopeKind::Global | ScopeKind::Module))
+            .flat_map(|scope| scope.all_symbols())
+    }
+
     /// Check for unused variables in the current scope
     pub fn find_unused_variables(&self) -> Vec<&Symbol> {
         // This is a simple implementation - a more sophisticated one would track usage
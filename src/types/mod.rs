@@ -22,7 +22,7 @@ use std::collections::HashMap;
 use std::fmt;
 
 /// Ownership kind for AetherScript's ownership system
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum OwnershipKind {
     /// ^T - Single owner, value is moved on assignment
     Owned,
@@ -35,7 +35,7 @@ pub enum OwnershipKind {
 }
 
 /// Type constraint information for generic parameters
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum TypeConstraintInfo {
     /// Type must implement a trait/interface
     TraitBound { trait_name: String, module: Option<String> },
@@ -52,7 +52,7 @@ pub enum TypeConstraintInfo {
 }
 
 /// Type representation in the type system
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Type {
     /// Primitive types
     Primitive(PrimitiveType),
@@ -229,6 +229,15 @@ impl Type {
         }
     }
     
+    /// Width of a signed integer type as the backend lays it out
+    pub fn signed_bits(&self) -> Option<u32> {
+        match self {
+            Type::Primitive(PrimitiveType::Integer | PrimitiveType::Integer32) => Some(32),
+            Type::Primitive(PrimitiveType::Integer64) => Some(64),
+            _ => None,
+        }
+    }
+    
     /// Check if this type is a floating point type
     pub fn is_float(&self) -> bool {
         match self {
@@ -293,6 +302,17 @@ impl Type {
         }
     }
     
+    /// Bytes a value of this type occupies in a struct field of generated
+    /// code. Strings, collections and structs themselves are stored as
+    /// pointers.
+    pub fn storage_size(&self) -> usize {
+        match self {
+            Type::Primitive(PrimitiveType::Integer | PrimitiveType::Integer32 | PrimitiveType::Float32 | PrimitiveType::Boolean) => 4,
+            Type::Primitive(PrimitiveType::Char) => 1,
+            _ => 8,
+        }
+    }
+    
     /// Extract ownership information from a type
     pub fn get_ownership(&self) -> Option<OwnershipKind> {
         match self {
@@ -386,7 +406,7 @@ impl fmt::Display for Type {
 }
 
 /// Type variable for type inference
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct TypeVariable {
     pub id: usize,
     pub constraints: Vec<Type>,
@@ -456,6 +476,78 @@ pub enum TypeDefinition {
     },
 }
 
+/// Find the definition of a possibly module-qualified named type. Imported
+/// types can be named `module.Type` while their definition is registered
+/// under the bare name, or the other way around.
+pub fn find_type_definition<'a>(
+    definitions: &'a HashMap<String, TypeDefinition>,
+    name: &str,
+    module: Option<&str>,
+) -> Option<&'a TypeDefinition> {
+    definitions.get(name)
+        .or_else(|| module.and_then(|module| definitions.get(&format!("{}.{}", module, name))))
+        .or_else(|| name.rsplit_once('.').and_then(|(_, bare)| definitions.get(bare)))
+}
+
+/// Byte offset of each field of a struct and the struct's size, as code
+/// generation lays them out: every field is rounded up to a multiple of the
+/// previous field's size, at most 8, and the struct is not padded at the end
+pub fn struct_field_layout<'a>(field_types: impl IntoIterator<Item = &'a Type>) -> (Vec<usize>, usize) {
+    let mut offsets = Vec::new();
+    let mut offset = 0;
+    let mut end = 0;
+    for field_type in field_types {
+        offsets.push(offset);
+        let size = field_type.storage_size();
+        end = offset + size;
+        offset = end.next_multiple_of(size.min(8));
+    }
+    (offsets, end)
+}
+
+/// Success and failure variants of an enum that error propagation can
+/// unwrap: exactly two variants, one of them named `Ok` or `Some`
+pub fn propagation_variants(definition: &TypeDefinition) -> Option<(&EnumVariantInfo, &EnumVariantInfo)> {
+    match definition {
+        TypeDefinition::Enum { variants, .. } if variants.len() == 2 => {
+            let is_success = |variant: &EnumVariantInfo| variant.name == "Ok" || variant.name == "Some";
+            match (is_success(&variants[0]), is_success(&variants[1])) {
+                (true, false) => Some((&variants[0], &variants[1])),
+                (false, true) => Some((&variants[1], &variants[0])),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Name of the enum that holds an optional `payload`: a `Some` variant
+/// carrying it and a `None` variant carrying nothing. Fallible lookups need
+/// a single result type, so exactly one such enum must exist; otherwise the
+/// error says why none could be chosen.
+pub fn optional_enum_for<'a>(
+    definitions: &'a HashMap<String, TypeDefinition>,
+    payload: &Type,
+) -> Result<&'a str, String> {
+    let bare = |name: &str| name.rsplit('.').next().unwrap_or(name).to_string();
+    let mut names: Vec<&str> = definitions.iter()
+        .filter(|(_, definition)| matches!(
+            propagation_variants(definition),
+            Some((some, none)) if some.name == "Some" && some.associated_type.as_ref() == Some(payload)
+                && none.name == "None" && none.associated_type.is_none()
+        ))
+        .map(|(name, _)| name.as_str())
+        .collect();
+    // A definition may be registered under both its bare and qualified name
+    names.sort_by_key(|name| (bare(name), name.len()));
+    names.dedup_by_key(|name| bare(name));
+    match names.as_slice() {
+        [name] => Ok(name),
+        [] => Err(format!("no enum has a Some({}) variant and a None variant", payload)),
+        _ => Err(format!("several enums can hold an optional {}: {}", payload, names.join(", "))),
+    }
+}
+
 impl TypeChecker {
     /// Create a new type checker
     pub fn new() -> Self {
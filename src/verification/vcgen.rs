@@ -182,6 +182,17 @@ impl VcGenerator {
                     self.process_block(function, targets.otherwise, visited, vcs, contract)?;
                 }
             }
+            Terminator::CondBranch { cond, true_block, false_block } => {
+                let cond_formula = self.operand_to_formula(cond)?;
+                
+                self.path_condition.push(cond_formula.clone());
+                self.process_block(function, *true_block, visited, vcs, contract)?;
+                self.path_condition.pop();
+                
+                self.path_condition.push(Formula::Not(Box::new(cond_formula)));
+                self.process_block(function, *false_block, visited, vcs, contract)?;
+                self.path_condition.pop();
+            }
             Terminator::Goto { target } => {
                 self.process_block(function, *target, visited, vcs, contract)?;
             }
@@ -367,6 +378,10 @@ impl VcGenerator {
                     }
                     mir::ConstantValue::Char(c) => Formula::Int(*c as i64),
                     mir::ConstantValue::Null => Formula::Bool(false),
+                    mir::ConstantValue::Array(_) | mir::ConstantValue::Map(_) => {
+                        // Collections not yet supported in verification
+                        Formula::Bool(true)
+                    }
                 })
             }
         }
@@ -462,7 +477,7 @@ impl VcGenerator {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::mir::{Function, BasicBlock};
+    use crate::mir::{Function, BasicBlock, CallingConvention, PassControl};
     
     #[test]
     fn test_vc_generator_creation() {
@@ -485,6 +500,10 @@ mod tests {
             basic_blocks: HashMap::new(),
             entry_block: 0,
             return_local: None,
+            is_pure: false,
+            export_symbol: None,
+            calling_convention: CallingConvention::Rust,
+            pass_control: PassControl::default(),
         };
         
         // Add an empty entry block
@@ -52,6 +52,8 @@ fn create_control_flow_ast() -> Program {
             throws_exceptions: Vec::new(),
             thread_safe: None,
             may_block: None,
+            pure: None,
+            optimization_hints: vec![],
         },
         body: Block {
             statements: vec![
@@ -63,6 +65,7 @@ fn create_control_flow_ast() -> Program {
                         }),
                         right: Box::new(Expression::IntegerLiteral {
                             value: 10,
+                            suffix: None,
                             source_location: loc.clone(),
                         }),
                         source_location: loc.clone(),
@@ -72,6 +75,7 @@ fn create_control_flow_ast() -> Program {
                             Statement::Return {
                                 value: Some(Box::new(Expression::IntegerLiteral {
                                     value: 1,
+                                    suffix: None,
                                     source_location: loc.clone(),
                                 })),
                                 source_location: loc.clone(),
@@ -85,6 +89,7 @@ fn create_control_flow_ast() -> Program {
                             Statement::Return {
                                 value: Some(Box::new(Expression::IntegerLiteral {
                                     value: 0,
+                                    suffix: None,
                                     source_location: loc.clone(),
                                 })),
                                 source_location: loc.clone(),
@@ -133,6 +138,8 @@ fn create_control_flow_ast() -> Program {
             throws_exceptions: Vec::new(),
             thread_safe: None,
             may_block: None,
+            pure: None,
+            optimization_hints: vec![],
         },
         body: Block {
             statements: vec![
@@ -145,6 +152,7 @@ fn create_control_flow_ast() -> Program {
                     mutability: Mutability::Mutable,
                     initial_value: Some(Box::new(Expression::IntegerLiteral {
                         value: 0,
+                        suffix: None,
                         source_location: loc.clone(),
                     })),
                     intent: None,
@@ -176,6 +184,7 @@ fn create_control_flow_ast() -> Program {
                                     }),
                                     right: Box::new(Expression::IntegerLiteral {
                                         value: 1,
+                                        suffix: None,
                                         source_location: loc.clone(),
                                     }),
                                     source_location: loc.clone(),
@@ -258,12 +267,15 @@ fn test_non_boolean_condition_error() {
             throws_exceptions: Vec::new(),
             thread_safe: None,
             may_block: None,
+            pure: None,
+            optimization_hints: vec![],
         },
         body: Block {
             statements: vec![
                 Statement::If {
                     condition: Box::new(Expression::IntegerLiteral {
                         value: 42, // This should fail - not a boolean
+                        suffix: None,
                         source_location: loc.clone(),
                     }),
                     then_block: Block {
@@ -335,6 +347,8 @@ fn test_loop_variable_scope() {
             throws_exceptions: Vec::new(),
             thread_safe: None,
             may_block: None,
+            pure: None,
+            optimization_hints: vec![],
         },
         body: Block {
             statements: vec![
@@ -347,14 +361,17 @@ fn test_loop_variable_scope() {
                         elements: vec![
                             Box::new(Expression::IntegerLiteral {
                                 value: 1,
+                                suffix: None,
                                 source_location: loc.clone(),
                             }),
                             Box::new(Expression::IntegerLiteral {
                                 value: 2,
+                                suffix: None,
                                 source_location: loc.clone(),
                             }),
                             Box::new(Expression::IntegerLiteral {
                                 value: 3,
+                                suffix: None,
                                 source_location: loc.clone(),
                             }),
                         ],
@@ -441,6 +458,8 @@ fn test_break_continue_statements() {
             throws_exceptions: Vec::new(),
             thread_safe: None,
             may_block: None,
+            pure: None,
+            optimization_hints: vec![],
         },
         body: Block {
             statements: vec![
@@ -536,6 +555,8 @@ fn test_try_catch_analysis() {
             throws_exceptions: Vec::new(),
             thread_safe: None,
             may_block: None,
+            pure: None,
+            optimization_hints: vec![],
         },
         body: Block {
             statements: vec![
@@ -545,6 +566,7 @@ fn test_try_catch_analysis() {
                             Statement::Return {
                                 value: Some(Box::new(Expression::IntegerLiteral {
                                     value: 42,
+                                    suffix: None,
                                     source_location: loc.clone(),
                                 })),
                                 source_location: loc.clone(),
@@ -564,6 +586,7 @@ fn test_try_catch_analysis() {
                                     Statement::Return {
                                         value: Some(Box::new(Expression::IntegerLiteral {
                                             value: -1,
+                                            suffix: None,
                                             source_location: loc.clone(),
                                         })),
                                         source_location: loc.clone(),
@@ -266,6 +266,7 @@ proptest! {
                     }),
                     value: Box::new(aether::ast::Expression::IntegerLiteral {
                         value: 42,
+                        suffix: None,
                         source_location: aether::error::SourceLocation::unknown(),
                     }),
                     intent: Some("Test constant".to_string()),
@@ -92,6 +92,8 @@ fn create_test_module_with_struct() -> Module {
                     throws_exceptions: vec![],
                     thread_safe: Some(true),
                     may_block: Some(false),
+                    pure: None,
+                    optimization_hints: vec![],
                 },
                 body: Block {
                     statements: vec![